@@ -70,3 +70,11 @@ pub const DEPENDENT_VERSION: VersionTuple = VersionTuple(0, 4, 52);
 pub const DEFAULT_PARSER_OPTIONS: Options = Options::from_bits_truncate(
     Options::ENABLE_FOOTNOTES.bits() | Options::ENABLE_MATH.bits() | Options::ENABLE_GFM.bits(),
 );
+
+/// The parser config for headings, the same as `DEFAULT_PARSER_OPTIONS` but with `{#id}` syntax.
+///
+/// mdbook always parses heading attributes, so both `tag_adder` and the anchor index
+/// must use it to agree with the rendered book on every ID.
+pub const HEADING_PARSER_OPTIONS: Options = Options::from_bits_truncate(
+    DEFAULT_PARSER_OPTIONS.bits() | Options::ENABLE_HEADING_ATTRIBUTES.bits(),
+);
@@ -1,4 +1,5 @@
-use clap::{Arg, Command, command};
+use clap::{Arg, ArgAction, Command, command, value_parser};
+use std::path::PathBuf;
 
 /// Parse the command.
 ///
@@ -11,5 +12,27 @@ pub fn make_app() -> Command {
                 .arg(Arg::new("renderer").required(true))
                 .about("Only for mdbook preprocessor"),
         )
-        .subcommand(Command::new("check").about("Manual do link check. Not supported at this time."))
+        .subcommand(
+            Command::new("check")
+                .arg(
+                    Arg::new("dir")
+                        .default_value(".")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Root directory of the book (where `book.toml` is)"),
+                )
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .action(ArgAction::SetTrue)
+                        .help("Rewrite the links that have an unambiguous suggestion"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .requires("fix")
+                        .help("Only show the fixes as a unified diff, without writing files"),
+                )
+                .about("Manual do link check for the whole book."),
+        )
 }
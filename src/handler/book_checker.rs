@@ -0,0 +1,117 @@
+use super::config::ProcessorConfig;
+use crate::link_checker::anchors::AnchorIndex;
+use crate::link_checker::fixer;
+use crate::link_checker::issue::LinkIssue;
+use mdbook::MDBook;
+use mdbook::book::BookItem;
+use mdbook::config::Config;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Loads the book at `book_dir` without touching any file on the disk.
+///
+/// Unlike `MDBook::load`, missing chapters are **not** created,
+/// otherwise the links to them could never be reported.
+pub fn load_book(book_dir: &Path) -> mdbook::errors::Result<MDBook> {
+    // Links are resolved to absolute paths, so the root must be absolute too.
+    let book_dir = book_dir.canonicalize()?;
+    let config_path = book_dir.join("book.toml");
+    let mut config = if config_path.exists() {
+        Config::from_disk(&config_path)?
+    } else {
+        Config::default()
+    };
+    config.update_from_env();
+    config.build.create_missing = false;
+    MDBook::load_with_config(&book_dir, config)
+}
+
+/// Checks the links of the whole book: `SUMMARY.md` and every chapter.
+/// The external links are requested too if `check_external` is on.
+/// At last, the pages and assets under `src/` that nothing refers to are reported.
+///
+/// The `do_link_check` config is ignored here, because the check is requested manually.
+///
+/// # Returns
+/// All issues found in the book.
+pub fn check_book(md: &MDBook, config: &ProcessorConfig) -> Vec<LinkIssue> {
+    // Canonicalized once, since resolved links are canonical (e.g. for `src = "src/../src"`).
+    let src = md.root.join(&md.config.book.src);
+    let src = src.canonicalize().unwrap_or(src);
+    let mut conf = config.link_checker_config.clone();
+    conf.set_book_dirs(&md.root, &md.config);
+    let conf = &conf;
+    let index = AnchorIndex::from_book(
+        &md.book,
+        config.add_link_for_chinese,
+        config.use_old_tag_adder,
+    );
+    let mut issues = Vec::new();
+    let mut found = Vec::new();
+
+    match fs::read_to_string(src.join("SUMMARY.md")) {
+        Ok(summary) => {
+            issues.extend(crate::link_checker::check_link(
+                &summary,
+                &Some(PathBuf::from("SUMMARY.md")),
+                &src,
+                &index,
+                conf,
+                &mut found,
+            ));
+        }
+        Err(e) => log::warn!("Failed to read `SUMMARY.md`: {}", e),
+    }
+
+    for item in md.iter() {
+        if let BookItem::Chapter(chapter) = item {
+            issues.extend(crate::link_checker::check_link(
+                &chapter.content,
+                &chapter.source_path,
+                &src,
+                &index,
+                conf,
+                &mut found,
+            ));
+        }
+    }
+
+    if conf.check_external {
+        issues.extend(crate::link_checker::external::check_external_links(
+            &found, conf, &md.root, &src,
+        ));
+    }
+
+    let chapters = md.iter().filter_map(|item| match item {
+        BookItem::Chapter(chapter) => chapter.source_path.as_deref(),
+        _ => None,
+    });
+    issues.extend(crate::link_checker::orphans::check_orphans(
+        chapters, &found, &src, conf,
+    ));
+
+    issues
+}
+
+/// Fixes the issues that have a suggestion in the source files under `src`.
+///
+/// With `dry_run`, the fixes are printed as a unified diff and no file is written.
+///
+/// # Returns
+/// How many issues are fixed.
+pub fn fix_book(issues: &[LinkIssue], src: &Path, dry_run: bool) -> usize {
+    let mut total = 0;
+    for fix in fixer::plan_fixes(issues, src) {
+        let path = fix.file.to_string_lossy().replace('\\', "/");
+        if dry_run {
+            print!("{}", fixer::unified_diff(&path, &fix.original, &fix.fixed));
+        } else if let Err(e) = fs::write(src.join(&fix.file), &fix.fixed) {
+            log::error!("Failed to write `{}`: {}", path, e);
+            continue;
+        } else {
+            log::info!("Fixed {} link(s) in `{}`.", fix.count, path);
+        }
+        total += fix.count;
+    }
+    total
+}
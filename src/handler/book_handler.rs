@@ -1,36 +1,111 @@
 use super::config::ProcessorConfig;
+use crate::link_checker::FoundLink;
+use crate::link_checker::anchors::AnchorIndex;
+use crate::link_checker::issue::{self, LinkIssue};
 use mdbook::book::{Book, BookItem, Chapter};
 use mdbook::preprocess::PreprocessorContext;
 
 pub mod old_tag_adder;
 pub mod tag_adder;
 
-fn chapter_handle(chapter: &mut Chapter, config: &ProcessorConfig, src: &std::path::Path) {
+/// Handles a chapter: checks its links, then adds the anchor tags.
+///
+/// The links are checked before the tags are added,
+/// so that the reported positions point at the chapter source rather than the rewritten content.
+/// The content has been expanded by mdbook's `links` preprocessor already, though,
+/// so positions after a `{{#include}}` (or the like) point into the expanded text.
+/// The `check` command reads the files as they are on disk, and has no such limitation.
+/// The checked links are appended to `found`.
+fn chapter_handle(
+    chapter: &mut Chapter,
+    config: &ProcessorConfig,
+    src: &std::path::Path,
+    index: &AnchorIndex,
+    found: &mut Vec<FoundLink>,
+) -> Vec<LinkIssue> {
+    let issues = if config.do_link_check {
+        crate::link_checker::check_link(
+            &chapter.content,
+            &chapter.source_path,
+            src,
+            index,
+            &config.link_checker_config,
+            found,
+        )
+    } else {
+        Vec::new()
+    };
+
     if config.use_old_tag_adder {
         old_tag_adder::add_a_tag(&mut chapter.content, config.add_link_for_chinese)
     } else {
-        tag_adder::add_heading_anchors(&mut chapter.content, config.add_link_for_chinese);
+        match chapter
+            .source_path
+            .as_ref()
+            .and_then(|path| index.get(path))
+        {
+            Some(anchors) => tag_adder::add_heading_anchors_reserved(
+                &mut chapter.content,
+                config.add_link_for_chinese,
+                &anchors.explicit,
+            ),
+            None => {
+                tag_adder::add_heading_anchors(&mut chapter.content, config.add_link_for_chinese)
+            }
+        }
     }
     if config.display_processed_contents {
         log::debug!("new content: {0}", chapter.content);
     }
-    if config.do_link_check {
-        crate::link_checker::check_link(
-            &chapter.content,
-            &chapter.source_path,
-            src,
-            &config.link_checker_config,
-        );
-    }
+
+    issues
 }
 
 /// Preprocessed core handle.
-pub fn handle(mut book: Book, config: ProcessorConfig, ctx: &PreprocessorContext) -> Book {
+///
+/// The anchors of the whole book are indexed first,
+/// then every chapter is handled with the help of the index.
+/// The external links are requested at last (if `check_external` is on).
+///
+/// Fails if the link issues meet the `fail_on` policy.
+pub fn handle(
+    mut book: Book,
+    mut config: ProcessorConfig,
+    ctx: &PreprocessorContext,
+) -> mdbook::errors::Result<Book> {
+    // Canonicalized once, since resolved links are canonical (e.g. for a root like `/b/src/..`).
     let src = ctx.root.join(&ctx.config.book.src);
+    let src = src.canonicalize().unwrap_or(src);
+    config
+        .link_checker_config
+        .set_book_dirs(&ctx.root, &ctx.config);
+    let index =
+        AnchorIndex::from_book(&book, config.add_link_for_chinese, config.use_old_tag_adder);
+    let mut issues = Vec::new();
+    let mut found = Vec::new();
     book.for_each_mut(|book_item| {
         if let BookItem::Chapter(chapter) = book_item {
-            chapter_handle(chapter, &config, &src)
+            issues.extend(chapter_handle(chapter, &config, &src, &index, &mut found));
         }
     });
-    book
+
+    let conf = &config.link_checker_config;
+    if config.do_link_check && conf.check_external {
+        issues.extend(crate::link_checker::external::check_external_links(
+            &found, conf, &ctx.root, &src,
+        ));
+    }
+
+    crate::link_checker::report::write_configured_report(
+        &issues,
+        &config.link_checker_config,
+        &ctx.root,
+        &ctx.config.book.src,
+    );
+
+    let fail_on = config.link_checker_config.fail_on;
+    if issues.iter().any(|issue| fail_on.is_met(issue.level)) {
+        return Err(mdbook::errors::Error::msg(issue::summarize(&issues)));
+    }
+    Ok(book)
 }
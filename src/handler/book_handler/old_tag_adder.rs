@@ -1,7 +1,18 @@
 pub fn add_a_tag(content: &mut String, check_language: bool) {
+    *content = add_tags(content, check_language).0;
+}
+
+/// Returns the IDs of the anchors that `add_a_tag` inserts into `content`.
+pub fn anchor_ids(content: &str, check_language: bool) -> Vec<String> {
+    add_tags(content, check_language).1
+}
+
+/// Adds the anchors, returns the new content and the IDs of the anchors.
+fn add_tags(content: &str, check_language: bool) -> (String, Vec<String>) {
     let mut in_code_block = false;
     let mut new_content = String::new();
     let mut id_counter = 0;
+    let mut ids = Vec::new();
 
     for line in content.lines() {
         // Handling Code Block Markers
@@ -72,7 +83,8 @@ pub fn add_a_tag(content: &mut String, check_language: bool) {
         );
         new_content.push_str(&new_line);
         new_content.push('\n');
+        ids.push(id);
     }
 
-    *content = new_content;
+    (new_content, ids)
 }
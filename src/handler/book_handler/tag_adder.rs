@@ -1,5 +1,5 @@
 use pulldown_cmark::{CowStr, Event, HeadingLevel, Tag, TagEnd};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Checks if the given text contains Chinese characters (CJK Unified Ideographs).
 /// This includes:
@@ -15,8 +15,11 @@ pub fn contains_chinese(text: &str) -> bool {
 ///
 /// It works by:
 /// 1. Collecting plain text content within a heading
-/// 2. Generating a URL-safe ID (slug) from the text or using `{#id}` syntax
+/// 2. Generating a URL-safe ID (slug) from the text
 /// 3. Inserting `<a id="..."></a>` immediately after the closing `</hN>` tag
+///
+/// Headings with `{#id}` syntax get no anchor: mdbook already gives the heading that ID,
+/// and anything after the `{#id}` would stop it from being parsed as an attribute.
 pub struct HeadingProcessor {
     in_code_block: bool, // Tracks whether current position is inside a code block
     current_level: HeadingLevel, // Current heading level (H1-H6)
@@ -24,6 +27,7 @@ pub struct HeadingProcessor {
     heading_text: String, // Accumulated plain text of the heading
     is_in_heading: bool, // Whether currently processing a heading
     seen_ids: HashMap<String, usize>, // Count of each generated ID to avoid duplicates
+    anchor_ids: Vec<String>, // IDs of all inserted anchors, in document order
 }
 
 impl HeadingProcessor {
@@ -36,9 +40,22 @@ impl HeadingProcessor {
             heading_text: String::new(),
             is_in_heading: false,
             seen_ids: HashMap::new(),
+            anchor_ids: Vec::new(),
+        }
+    }
+
+    /// Reserves IDs (e.g. explicit `{#id}` ones of the chapter) so that no generated ID takes them.
+    pub fn reserve_ids(&mut self, ids: &HashSet<String>) {
+        for id in ids {
+            self.seen_ids.entry(id.clone()).or_insert(1);
         }
     }
 
+    /// Returns the generated IDs of all anchors inserted so far.
+    pub fn anchor_ids(&self) -> &[String] {
+        &self.anchor_ids
+    }
+
     /// Processes a single event in the Markdown AST.
     /// Modifies heading start events to include `id` attribute when needed.
     ///
@@ -140,17 +157,15 @@ impl HeadingProcessor {
         level: HeadingLevel,
         check_chinese: bool,
     ) {
-        let should_add_id = !check_chinese || contains_chinese(self.heading_text.trim());
+        let should_add_id = self.provided_id.is_none()
+            && (!check_chinese || contains_chinese(self.heading_text.trim()));
 
-        let generated_id = if should_add_id && self.provided_id.is_none() {
-            Some(self.generate_unique_id())
-        } else {
-            self.provided_id.clone()
-        };
+        let generated_id = should_add_id.then(|| self.generate_unique_id());
 
         output.push(Event::End(TagEnd::Heading(level)));
 
         if let Some(id) = generated_id {
+            self.anchor_ids.push(id.to_string());
             let anchor_html = format!(r#"<a id="{}"></a>"#, id);
             output.push(Event::Html(anchor_html.into()));
         }
@@ -205,8 +220,22 @@ impl Default for HeadingProcessor {
 /// * `content` - Mutable reference to the Markdown content (will be overwritten with HTML)
 /// * `check_chinese` - If true, only headings containing Chinese characters will get anchors
 pub fn add_heading_anchors(content: &mut String, check_chinese: bool) {
-    let parser = pulldown_cmark::Parser::new_ext(content, crate::attributes::DEFAULT_PARSER_OPTIONS);
+    add_heading_anchors_reserved(content, check_chinese, &HashSet::new());
+}
+
+/// Same as `add_heading_anchors`, but the generated IDs never take one of `reserved_ids`.
+///
+/// `reserved_ids` are usually the explicit IDs of the chapter from the `AnchorIndex`,
+/// so that the inserted anchors and the link checker agree on every ID.
+pub fn add_heading_anchors_reserved(
+    content: &mut String,
+    check_chinese: bool,
+    reserved_ids: &HashSet<String>,
+) {
+    let parser =
+        pulldown_cmark::Parser::new_ext(content, crate::attributes::HEADING_PARSER_OPTIONS);
     let mut processor = HeadingProcessor::new();
+    processor.reserve_ids(reserved_ids);
     let mut events = Vec::new();
 
     for event in parser {
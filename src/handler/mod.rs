@@ -1,9 +1,11 @@
+pub mod book_checker;
 pub mod book_handler;
 pub mod config;
 pub mod processor;
 
 use mdbook::preprocess::Preprocessor;
 use std::io;
+use std::path::Path;
 use std::process;
 
 /// Exits the process with an error status after logging the error.
@@ -26,12 +28,71 @@ pub fn handle() {
 
     // Process the book data
     let handler = processor::Handler::new();
-    let processed_book = handler.run(&ctx, book.clone()).unwrap_or_else(|e| {
-        log::warn!("Processing failed, using original book: {}", e);
-        book
-    });
+    let processed_book = handler
+        .run(&ctx, book)
+        .unwrap_or_else(|e| exit_with_error(&format!("Processing failed: {}", e)));
 
     // Write output data
     serde_json::to_writer(io::stdout(), &processed_book)
         .unwrap_or_else(|e| exit_with_error(&format!("Failed to write output: {}", e)));
 }
+
+/// How the `check` command fixes the links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixMode {
+    /// Don't fix (only check).
+    Off,
+    /// Rewrite the source files.
+    Write,
+    /// Only print the fixes as a unified diff.
+    DryRun,
+}
+
+/// Checks the links of the book in `book_dir` and exits with an error status
+/// if any issue meets the `fail_on` policy (see `FailOn::for_check`).
+///
+/// With `fix_mode`, the links that have an unambiguous suggestion are fixed first.
+pub fn check(book_dir: &Path, fix_mode: FixMode) {
+    let mut md = book_checker::load_book(book_dir)
+        .unwrap_or_else(|e| exit_with_error(&format!("Failed to load the book: {}", e)));
+    let config = processor::get_processor_config(&md.config);
+
+    let mut issues = book_checker::check_book(&md, &config);
+    if fix_mode != FixMode::Off {
+        let src = md.root.join(&md.config.book.src);
+        let fixed = book_checker::fix_book(&issues, &src, fix_mode == FixMode::DryRun);
+        if fixed > 0 && fix_mode == FixMode::Write {
+            // Check again, the fixed issues are gone and the positions may have changed.
+            md = book_checker::load_book(book_dir)
+                .unwrap_or_else(|e| exit_with_error(&format!("Failed to load the book: {}", e)));
+            issues = book_checker::check_book(&md, &config);
+        }
+    }
+
+    crate::link_checker::report::write_configured_report(
+        &issues,
+        &config.link_checker_config,
+        &md.root,
+        &md.config.book.src,
+    );
+    let fail_on = config.link_checker_config.fail_on.for_check();
+    let failed = issues
+        .iter()
+        .filter(|issue| fail_on.is_met(issue.level))
+        .count();
+    if failed > 0 {
+        exit_with_error(&format!(
+            "Found {} link issue(s), {} of them meet the `fail_on` policy.",
+            issues.len(),
+            failed
+        ));
+    }
+    if issues.is_empty() {
+        log::info!("No link issues found.");
+    } else {
+        log::info!(
+            "Found {} link issue(s), none of them meet the `fail_on` policy.",
+            issues.len()
+        );
+    }
+}
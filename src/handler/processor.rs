@@ -34,7 +34,7 @@ fn check_version(current_mdbook_version: &attributes::VersionTuple) {
 }
 
 use super::config::{ProcessorConfig, parse_config};
-fn get_processor_config(config: &Config) -> ProcessorConfig {
+pub fn get_processor_config(config: &Config) -> ProcessorConfig {
     parse_config(
         config
             .get("preprocessor.betterlink")
@@ -55,6 +55,6 @@ impl Preprocessor for Handler {
 
         let configs = get_processor_config(&ctx.config);
 
-        Ok(super::book_handler::handle(book, configs, ctx))
+        super::book_handler::handle(book, configs, ctx)
     }
 }
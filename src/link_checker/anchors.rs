@@ -0,0 +1,198 @@
+use crate::handler::book_handler::old_tag_adder;
+use crate::handler::book_handler::tag_adder::HeadingProcessor;
+use mdbook::book::{Book, BookItem};
+use pulldown_cmark::{Event, Tag, TagEnd};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The anchors of a chapter.
+#[derive(Clone, Debug, Default)]
+pub struct ChapterAnchors {
+    /// IDs given explicitly by the `{#id}` syntax.
+    pub explicit: HashSet<String>,
+    /// All IDs (explicit and generated) of the chapter.
+    pub all: HashSet<String>,
+}
+
+/// Book-wide index: chapter path (relative to `src/`) -> anchors of the chapter.
+///
+/// It is built once before any chapter is processed,
+/// so that both `tag_adder` and `link_checker` can see the anchors of other chapters.
+#[derive(Clone, Debug, Default)]
+pub struct AnchorIndex {
+    chapters: HashMap<PathBuf, ChapterAnchors>,
+    check_chinese: bool,
+    old_tag_adder: bool,
+}
+
+impl AnchorIndex {
+    /// Creates an empty index.
+    /// `check_chinese` should be the same as the `add_link_for_chinese` config.
+    pub fn new(check_chinese: bool) -> Self {
+        Self {
+            chapters: HashMap::new(),
+            check_chinese,
+            old_tag_adder: false,
+        }
+    }
+
+    /// Indexes the anchors of `old_tag_adder::add_a_tag` instead of `tag_adder`.
+    /// `old_tag_adder` should be the same as the `use_old_tag_adder` config.
+    pub fn with_old_tag_adder(mut self, old_tag_adder: bool) -> Self {
+        self.old_tag_adder = old_tag_adder;
+        self
+    }
+
+    /// Builds the index from every chapter (with a source path) of the book.
+    /// `old_tag_adder` should be the same as the `use_old_tag_adder` config.
+    pub fn from_book(book: &Book, check_chinese: bool, old_tag_adder: bool) -> Self {
+        let mut index = Self::new(check_chinese).with_old_tag_adder(old_tag_adder);
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item
+                && let Some(path) = &chapter.source_path
+            {
+                index.insert(path.clone(), &chapter.content);
+            }
+        }
+        index
+    }
+
+    /// Collects the anchors of `content` and records them under `path`.
+    pub fn insert(&mut self, path: PathBuf, content: &str) {
+        let anchors = collect_chapter_anchors(content, self.check_chinese, self.old_tag_adder);
+        self.chapters.insert(path, anchors);
+    }
+
+    /// Gets the anchors of the chapter at `path` (relative to `src/`).
+    pub fn get(&self, path: &Path) -> Option<&ChapterAnchors> {
+        self.chapters.get(path)
+    }
+
+    /// Whether no chapter is indexed (e.g. a single file is checked without a book).
+    pub fn is_empty(&self) -> bool {
+        self.chapters.is_empty()
+    }
+
+    /// Checks whether the file at `path` (relative to `root`) has an anchor with `id`.
+    ///
+    /// Files that aren't in the index (e.g. not listed in `SUMMARY.md`) are read from the disk.
+    /// Returns `None` if the file can't be read.
+    pub fn has_anchor(&self, root: &Path, path: &Path, id: &str) -> Option<bool> {
+        Some(self.anchors(root, path)?.contains(id))
+    }
+
+    /// Gets all anchor IDs of the file at `path` (relative to `root`).
+    ///
+    /// Files that aren't in the index are read from the disk.
+    /// Returns `None` if the file can't be read.
+    pub fn anchors(&self, root: &Path, path: &Path) -> Option<Cow<'_, HashSet<String>>> {
+        if let Some(anchors) = self.get(path) {
+            return Some(Cow::Borrowed(&anchors.all));
+        }
+        let content = fs::read_to_string(root.join(path)).ok()?;
+        Some(Cow::Owned(collect_anchors(
+            &content,
+            self.check_chinese,
+            self.old_tag_adder,
+        )))
+    }
+}
+
+/// Collects all anchor IDs that the headings of `content` will have in the rendered book.
+///
+/// It includes:
+/// - Explicit IDs (e.g. `# Title {#id}`)
+/// - IDs generated by `tag_adder` (the same as `HeadingProcessor::generate_unique_id`),
+///   or by `old_tag_adder` with `old_tag_adder`
+/// - IDs generated by mdbook itself (e.g. `hello-world` for `# Hello World`)
+/// - IDs in raw HTML (e.g. `<a id="top"></a>` or `<a name="top"></a>`)
+///
+/// `check_chinese` should be the same as the `add_link_for_chinese` config,
+/// `old_tag_adder` the same as the `use_old_tag_adder` config.
+pub fn collect_anchors(content: &str, check_chinese: bool, old_tag_adder: bool) -> HashSet<String> {
+    collect_chapter_anchors(content, check_chinese, old_tag_adder).all
+}
+
+/// Same as `collect_anchors`, but keeps the explicit IDs apart.
+pub fn collect_chapter_anchors(
+    content: &str,
+    check_chinese: bool,
+    old_tag_adder: bool,
+) -> ChapterAnchors {
+    let options = crate::attributes::HEADING_PARSER_OPTIONS;
+
+    let explicit: HashSet<String> = pulldown_cmark::Parser::new_ext(content, options)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Heading { id: Some(id), .. }) => Some(id.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    // Generated IDs never take an explicit one, the same as `add_heading_anchors_reserved`.
+    let mut processor = HeadingProcessor::new();
+    processor.reserve_ids(&explicit);
+    let mut output = Vec::new();
+
+    let mut all = explicit.clone();
+    let mut native_counter: HashMap<String, usize> = HashMap::new();
+    let mut heading_text: Option<String> = None;
+    let mut html_block = String::new();
+
+    for event in pulldown_cmark::Parser::new_ext(content, options) {
+        match &event {
+            // mdbook only generates an ID for headings without `{#id}`.
+            Event::Start(Tag::Heading { id: None, .. }) => {
+                heading_text = Some(String::new());
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading_text) = heading_text.as_mut() {
+                    heading_text.push_str(text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = heading_text.take() {
+                    all.insert(unique_native_id(&text, &mut native_counter));
+                }
+            }
+            Event::Html(html) => html_block.push_str(html),
+            Event::End(TagEnd::HtmlBlock) => {
+                all.extend(html_anchors(&html_block));
+                html_block.clear();
+            }
+            Event::InlineHtml(html) => all.extend(html_anchors(html)),
+            _ => {}
+        }
+        if !old_tag_adder {
+            processor.process_heading_event(event.into_static(), &mut output, check_chinese);
+        }
+    }
+
+    if old_tag_adder {
+        all.extend(old_tag_adder::anchor_ids(content, check_chinese));
+    } else {
+        all.extend(processor.anchor_ids().iter().cloned());
+    }
+    ChapterAnchors { explicit, all }
+}
+
+/// Gets the anchor IDs defined in raw HTML.
+fn html_anchors(html: &str) -> impl Iterator<Item = String> + '_ {
+    super::html_scanner::scan_attributes(html)
+        .into_iter()
+        .filter(|attribute| attribute.is_anchor())
+        .map(|attribute| attribute.value.to_string())
+}
+
+/// Generates the heading ID in the same way as mdbook does.
+fn unique_native_id(text: &str, counter: &mut HashMap<String, usize>) -> String {
+    let id = mdbook::utils::normalize_id(text.trim());
+    let count = counter.entry(id.clone()).or_insert(0);
+    let unique_id = match *count {
+        0 => id,
+        count => format!("{}-{}", id, count),
+    };
+    *count += 1;
+    unique_id
+}
@@ -1,7 +1,154 @@
+use super::domain::DomainList;
+use super::issue::IssueKind;
+use super::pattern::PatternList;
+use super::report::ReportFormat;
 use log::Level;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use toml::value::Table;
 
+/// The policy about when the link issues fail the build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailOn {
+    /// Fail when any issue is prompted at `Level::Error`.
+    Error,
+    /// Fail when any issue is prompted at `Level::Warn` or higher.
+    Warning,
+    /// Never fail (only prompt).
+    Never,
+}
+
+impl FailOn {
+    /// Parse the policy name.
+    ///
+    /// Supports:
+    /// - `"error"`: `FailOn::Error`
+    /// - `"warning"`: `FailOn::Warning`
+    /// - `"never"`: `FailOn::Never`
+    /// - Others: Warn and use default policy.
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "error" => FailOn::Error,
+            "warning" => FailOn::Warning,
+            "never" => FailOn::Never,
+            _ => {
+                log::error!("Invalid fail_on policy! Will use default: \"never\".");
+                FailOn::Never
+            }
+        }
+    }
+
+    /// Whether an issue prompted at `level` meets the threshold.
+    pub fn is_met(&self, level: Level) -> bool {
+        match self {
+            FailOn::Error => level <= Level::Error,
+            FailOn::Warning => level <= Level::Warn,
+            FailOn::Never => false,
+        }
+    }
+
+    /// The policy of the `check` command.
+    ///
+    /// The command is run to fail CI, so `FailOn::Never` (the default for the build)
+    /// fails on errors there. Other policies are kept.
+    pub fn for_check(self) -> Self {
+        match self {
+            FailOn::Never => FailOn::Error,
+            other => other,
+        }
+    }
+}
+
+/// The policy about following symlinks when resolving link paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Follow symlinks, but the real target must be inside the root directory.
+    InsideRoot,
+    /// Follow symlinks anywhere (only the path as written must be inside the root directory).
+    Always,
+    /// Never follow symlinks: links through a symlink are prompted.
+    Never,
+}
+
+impl SymlinkPolicy {
+    /// Parse the policy name.
+    ///
+    /// Supports:
+    /// - `"inside-root"`: `SymlinkPolicy::InsideRoot`
+    /// - `"always"`: `SymlinkPolicy::Always`
+    /// - `"never"`: `SymlinkPolicy::Never`
+    /// - Others: Warn and use default policy.
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "inside-root" => SymlinkPolicy::InsideRoot,
+            "always" => SymlinkPolicy::Always,
+            "never" => SymlinkPolicy::Never,
+            _ => {
+                log::error!("Invalid follow_symlinks policy! Will use default: \"inside-root\".");
+                SymlinkPolicy::InsideRoot
+            }
+        }
+    }
+}
+
+/// It is about the configuration of the external link check.
+/// We're going to read `preprocessor.betterlink.link_checker.external` fields in the book's config.
+#[derive(Clone, Debug)]
+pub struct ExternalConfig {
+    /// Timeout of each request, in seconds.
+    /// Default: 10
+    pub timeout: u64,
+    /// How many requests are sent at the same time.
+    /// Default: 8
+    pub concurrency: usize,
+    /// How many times a request is retried after a temporary failure
+    /// (unreachable, `429` or `5xx`).
+    /// Default: 2
+    pub retries: u32,
+    /// The minimum interval between two requests to the same host, in milliseconds.
+    /// Default: 200
+    pub host_interval: u64,
+    /// How long a cached result is trusted, in seconds.
+    /// Default: 86400 (one day)
+    pub cache_ttl: u64,
+    /// The cache file (relative to the book root).
+    /// Default: `".betterlink-cache.json"`
+    pub cache_file: PathBuf,
+}
+
+impl ExternalConfig {
+    pub fn parse(raw_table: &Table) -> Self {
+        let default = Self::default();
+        let get_u64 = |key: &str, default: u64| {
+            u64::try_from(get_integer_config(raw_table, key, default as i64)).unwrap_or(default)
+        };
+        Self {
+            timeout: get_u64("timeout", default.timeout),
+            concurrency: get_u64("concurrency", default.concurrency as u64) as usize,
+            retries: get_u64("retries", u64::from(default.retries)) as u32,
+            host_interval: get_u64("host_interval", default.host_interval),
+            cache_ttl: get_u64("cache_ttl", default.cache_ttl),
+            cache_file: raw_table
+                .get("cache_file")
+                .and_then(|v| v.as_str())
+                .map_or(default.cache_file, PathBuf::from),
+        }
+    }
+}
+
+impl Default for ExternalConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 10,
+            concurrency: 8,
+            retries: 2,
+            host_interval: 200,
+            cache_ttl: 86400,
+            cache_file: PathBuf::from(".betterlink-cache.json"),
+        }
+    }
+}
+
 /// It is about the configuration of link checker.
 /// We're going to read `preprocessor.betterlink.link_checker` fields in the book's config.
 /// For processor or using by command (in the future), it can work.
@@ -13,12 +160,129 @@ pub struct LinkCheckerConfig {
     /// Default: `Level::Error` (or 1)
     pub prompt_level: Level,
     /// Black `url` list.
-    /// If a url is in this list, we'll prompt always.
+    /// If a url matches this list, we'll prompt always.
     /// Values in Array that are not strings are not converted (and **no warning**).
-    /// Default: `HashSet::default()` (or `[]`)
+    /// Default: `PatternList::default()` (or `[]`)
     ///
-    /// **Only an exact URL/path match is pulled into the blacklist.**
-    pub black_list: HashSet<String>,
+    /// Entries are exact URLs/paths, glob patterns (with `*`, `?` or `[`)
+    /// or regular expressions (with the `regex:` prefix). See `PatternList`.
+    pub black_list: PatternList,
+    /// Allowed `url` list (`ignore` is an alias, both are merged).
+    /// Issues of the links matching this list are never prompted,
+    /// even if they're in the black list.
+    /// The patterns are the same as `black_list`, and they're matched against
+    /// both the whole URL and the URL without the `#fragment`/`?query`.
+    /// Default: `PatternList::default()` (or `[]`)
+    pub allow_list: PatternList,
+    /// Files (relative to `src/`) that are never reported as orphan pages or unused assets.
+    /// The patterns are the same as `black_list` (e.g. `"drafts/*"`).
+    /// Default: `PatternList::default()` (or `[]`)
+    pub orphan_ignore: PatternList,
+    /// Require the case of every path component to match the real file name,
+    /// even on case-insensitive filesystems (web hosts are usually case-sensitive).
+    /// Default: true
+    pub strict_case: bool,
+    /// How symlinks are followed when resolving link paths.
+    /// It's parsed from `"inside-root"`, `"always"` or `"never"`.
+    /// Default: `SymlinkPolicy::InsideRoot` (or `"inside-root"`)
+    pub follow_symlinks: SymlinkPolicy,
+    /// The URL schemes that links may use (lowercase).
+    /// Links with other schemes (e.g. `file:` or `javascript:`) are prompted,
+    /// and `http:` links (if allowed) are prompted to use `https:`.
+    /// Default: `["https", "http", "mailto", "tel"]`
+    pub schemes: HashSet<String>,
+    /// Hosts that links must not point to (e.g. `"*.corp.local"`).
+    /// Default: `DomainList::default()` (or `[]`)
+    ///
+    /// Entries are hosts or `*.` and a domain, matching its subdomains. See `DomainList`.
+    pub deny_domains: DomainList,
+    /// The only hosts that links may point to, if it's not empty.
+    /// IP addresses are prompted unless they're listed here.
+    /// The patterns are the same as `deny_domains`, which wins if both match.
+    /// Default: `DomainList::default()` (or `[]`)
+    pub allow_domains: DomainList,
+    /// The URL that the book is published at (e.g. `"https://example.com/book/"`).
+    /// Absolute links under it are prompted, relative links are suggested instead.
+    /// Default: `None`
+    pub site_url: Option<url::Url>,
+    /// Prompt every email address (`mailto:` links and email autolinks),
+    /// e.g. to keep them out of published books.
+    /// Default: false
+    pub forbid_email: bool,
+    /// Prompt images without alt text (at most at `Level::Warn`).
+    /// Default: false
+    pub warn_empty_alt: bool,
+    /// When the link issues fail the build.
+    /// It's parsed from `"error"`, `"warning"` or `"never"`.
+    /// Default: `FailOn::Never` (or `"never"`)
+    pub fail_on: FailOn,
+    /// The file to write the link report to (relative to the book root).
+    /// No report is written if it's not set.
+    /// Default: `None`
+    pub report_path: Option<PathBuf>,
+    /// The format of the link report.
+    /// It's parsed from `"json"`, `"sarif"` or `"junit"`.
+    /// Default: `ReportFormat::Json` (or `"json"`)
+    pub report_format: ReportFormat,
+    /// Request the external (`http(s)`) links and prompt the failed ones.
+    /// It needs the network, so it's opt-in.
+    /// Default: false
+    pub check_external: bool,
+    /// The configuration of the external link check.
+    /// It's parsed from the `external` table.
+    pub external: ExternalConfig,
+    /// The severity of each rule, overriding `prompt_level`.
+    /// It's parsed from the `rules` table (e.g. `missing-anchor = "warn"`),
+    /// whose values are `"off"`, `"info"`, `"warn"` or `"error"`.
+    /// `None` means the rule is off.
+    /// Default: `HashMap::default()` (or `{}`)
+    pub rules: HashMap<IssueKind, Option<Level>>,
+    /// Directories (absolute) that links must never point into.
+    /// They're not read from this table, but set from the book config by `set_book_dirs`.
+    /// Default: `Vec::new()`
+    pub forbidden_dirs: Vec<PathBuf>,
+}
+
+/// Parse the severity of a rule.
+///
+/// Supports:
+/// - `"off"`: `None` (the rule is off)
+/// - `"info"`: `Level::Info`
+/// - `"warn"`: `Level::Warn`
+/// - `"error"`: `Level::Error`
+/// - Others: Warn and return `Err`.
+fn parse_severity(rule: &str, name: &str) -> Result<Option<Level>, ()> {
+    match name {
+        "off" => Ok(None),
+        "info" => Ok(Some(Level::Info)),
+        "warn" => Ok(Some(Level::Warn)),
+        "error" => Ok(Some(Level::Error)),
+        _ => {
+            log::error!(
+                "Invalid severity of rule `{}`: \"{}\"! Will ignore it.",
+                rule,
+                name
+            );
+            Err(())
+        }
+    }
+}
+
+fn get_rules_config(table: &Table, key: &str) -> HashMap<IssueKind, Option<Level>> {
+    let Some(rules) = table.get(key).and_then(|v| v.as_table()) else {
+        return HashMap::default();
+    };
+    rules
+        .iter()
+        .filter_map(|(rule, severity)| {
+            let Some(kind) = IssueKind::from_rule(rule) else {
+                log::error!("Unknown rule `{}`! Will ignore it.", rule);
+                return None;
+            };
+            let severity = parse_severity(rule, severity.as_str().unwrap_or_default()).ok()?;
+            Some((kind, severity))
+        })
+        .collect()
 }
 
 fn get_str_vec_config(table: &Table, key: &str, default: HashSet<String>) -> HashSet<String> {
@@ -34,6 +298,39 @@ fn get_str_vec_config(table: &Table, key: &str, default: HashSet<String>) -> Has
         .unwrap_or(default)
 }
 
+/// Parse the `site_url`, whose path always ends with `/` (e.g. `https://example.com/book/`).
+/// Invalid URLs are prompted and ignored.
+fn parse_site_url(site_url: &str) -> Option<url::Url> {
+    let mut url = match url::Url::parse(site_url) {
+        Ok(url) if url.has_host() => url,
+        _ => {
+            log::error!("Invalid site_url: \"{}\"! Will ignore it.", site_url);
+            return None;
+        }
+    };
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(url)
+}
+
+fn default_schemes() -> HashSet<String> {
+    ["https", "http", "mailto", "tel"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn get_bool_config(table: &Table, key: &str, default: bool) -> bool {
+    table.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+}
+
+fn get_str_config<'a>(table: &'a Table, key: &str, default: &'a str) -> &'a str {
+    table.get(key).and_then(|v| v.as_str()).unwrap_or(default)
+}
+
 fn get_integer_config(table: &Table, key: &str, default: i64) -> i64 {
     table
         .get(key)
@@ -45,8 +342,105 @@ impl LinkCheckerConfig {
     pub fn parse(raw_table: &Table) -> Self {
         Self {
             prompt_level: Self::parse_log_level(get_integer_config(raw_table, "prompt_level", 1)),
-            black_list: get_str_vec_config(raw_table, "black_list", HashSet::default()),
+            black_list: PatternList::parse(
+                get_str_vec_config(raw_table, "black_list", HashSet::default())
+                    .iter()
+                    .map(String::as_str),
+            ),
+            allow_list: PatternList::parse(
+                get_str_vec_config(raw_table, "allow_list", HashSet::default())
+                    .iter()
+                    .chain(&get_str_vec_config(raw_table, "ignore", HashSet::default()))
+                    .map(String::as_str),
+            ),
+            orphan_ignore: PatternList::parse(
+                get_str_vec_config(raw_table, "orphan_ignore", HashSet::default())
+                    .iter()
+                    .map(String::as_str),
+            ),
+            strict_case: get_bool_config(raw_table, "strict_case", true),
+            follow_symlinks: SymlinkPolicy::parse(get_str_config(
+                raw_table,
+                "follow_symlinks",
+                "inside-root",
+            )),
+            schemes: get_str_vec_config(raw_table, "schemes", default_schemes())
+                .iter()
+                .map(|scheme| scheme.to_lowercase())
+                .collect(),
+            deny_domains: DomainList::parse(
+                get_str_vec_config(raw_table, "deny_domains", HashSet::default())
+                    .iter()
+                    .map(String::as_str),
+            ),
+            allow_domains: DomainList::parse(
+                get_str_vec_config(raw_table, "allow_domains", HashSet::default())
+                    .iter()
+                    .map(String::as_str),
+            ),
+            site_url: raw_table
+                .get("site_url")
+                .and_then(|v| v.as_str())
+                .and_then(parse_site_url),
+            forbid_email: get_bool_config(raw_table, "forbid_email", false),
+            warn_empty_alt: get_bool_config(raw_table, "warn_empty_alt", false),
+            fail_on: FailOn::parse(get_str_config(raw_table, "fail_on", "never")),
+            report_path: raw_table
+                .get("report_path")
+                .and_then(|v| v.as_str())
+                .map(PathBuf::from),
+            report_format: ReportFormat::parse(get_str_config(raw_table, "report_format", "json")),
+            check_external: get_bool_config(raw_table, "check_external", false),
+            external: raw_table
+                .get("external")
+                .and_then(|v| v.as_table())
+                .map(ExternalConfig::parse)
+                .unwrap_or_default(),
+            rules: get_rules_config(raw_table, "rules"),
+            forbidden_dirs: Vec::new(),
+        }
+    }
+
+    /// Forbids the links into the `build-dir` and the theme directory of the book at `book_root`.
+    pub fn set_book_dirs(&mut self, book_root: &Path, config: &mdbook::Config) {
+        let theme_dir = config
+            .html_config()
+            .unwrap_or_default()
+            .theme_dir(book_root);
+        self.forbidden_dirs = [book_root.join(&config.build.build_dir), theme_dir]
+            .into_iter()
+            .map(|dir| dir.canonicalize().unwrap_or(dir))
+            .collect();
+    }
+
+    /// The level that issues of `kind` are prompted at, `None` if the rule is off.
+    ///
+    /// It's the severity in `rules` if set, otherwise `prompt_level`
+    /// (at most `Level::Warn` for `unused-reference`, `empty-alt`, `orphan-page`,
+    /// `unused-asset`, `unencoded-space`, `insecure-http` and `self-link`).
+    pub fn level_of(&self, kind: IssueKind) -> Option<Level> {
+        if let Some(severity) = self.rules.get(&kind) {
+            return *severity;
+        }
+        match kind {
+            IssueKind::UnusedReference
+            | IssueKind::EmptyAlt
+            | IssueKind::OrphanPage
+            | IssueKind::UnusedAsset
+            | IssueKind::UnencodedSpace
+            | IssueKind::InsecureHttp
+            | IssueKind::SelfLink => Some(self.prompt_level.max(Level::Warn)),
+            _ => Some(self.prompt_level),
+        }
+    }
+
+    /// Whether the issues of `url` are ignored by the `allow_list`.
+    pub fn is_allowed(&self, url: &str) -> bool {
+        if self.allow_list.is_empty() {
+            return false;
         }
+        let (path_part, _) = super::path_checker::split_target(url);
+        self.allow_list.is_match(url) || self.allow_list.is_match(path_part)
     }
 
     /// Parse the numeric Level
@@ -86,7 +480,24 @@ impl Default for LinkCheckerConfig {
     fn default() -> Self {
         Self {
             prompt_level: Level::Error,
-            black_list: HashSet::default(),
+            black_list: PatternList::default(),
+            allow_list: PatternList::default(),
+            orphan_ignore: PatternList::default(),
+            strict_case: true,
+            follow_symlinks: SymlinkPolicy::InsideRoot,
+            schemes: default_schemes(),
+            deny_domains: DomainList::default(),
+            allow_domains: DomainList::default(),
+            site_url: None,
+            forbid_email: false,
+            warn_empty_alt: false,
+            fail_on: FailOn::Never,
+            report_path: None,
+            report_format: ReportFormat::Json,
+            check_external: false,
+            external: ExternalConfig::default(),
+            rules: HashMap::default(),
+            forbidden_dirs: Vec::new(),
         }
     }
 }
@@ -0,0 +1,174 @@
+use super::path_checker::decode;
+
+/// The kinds of contact links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContactKind {
+    /// A `mailto:` link (or an email autolink, e.g. `<foo@example.com>`).
+    Email,
+    /// A `tel:` link.
+    Phone,
+}
+
+/// Strips the `scheme:` prefix of `url` (case-insensitive).
+fn strip_scheme<'a>(url: &'a str, scheme: &str) -> Option<&'a str> {
+    let (prefix, rest) = url.split_at_checked(scheme.len() + 1)?;
+    (prefix[..scheme.len()].eq_ignore_ascii_case(scheme) && prefix.ends_with(':')).then_some(rest)
+}
+
+/// Finds the kind of a contact link.
+pub fn contact_kind(url: &str) -> Option<ContactKind> {
+    if strip_scheme(url, "mailto").is_some() {
+        Some(ContactKind::Email)
+    } else if strip_scheme(url, "tel").is_some() {
+        Some(ContactKind::Phone)
+    } else {
+        None
+    }
+}
+
+/// The characters allowed in an atom of RFC 5322 (`atext`).
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-".contains(c)
+}
+
+/// Checks a `dot-atom` (e.g. `first.last`): atoms of `atext` joined by single dots.
+fn is_dot_atom(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .split('.')
+            .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Checks a `quoted-string` (e.g. `"john doe"`).
+fn is_quoted_string(text: &str) -> bool {
+    let Some(inner) = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    else {
+        return false;
+    };
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.next().is_none() => return false,
+            '\\' => {}
+            '"' => return false,
+            c if c.is_ascii_control() && c != '\t' => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Checks a domain: DNS labels (letters, digits and `-`, not at the ends) with at least one dot,
+/// or a domain literal (e.g. `[192.0.2.1]`).
+fn is_domain(domain: &str) -> bool {
+    if let Some(literal) = domain
+        .strip_prefix('[')
+        .and_then(|domain| domain.strip_suffix(']'))
+    {
+        return !literal.is_empty() && !literal.contains(['[', ']', '\\']);
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+}
+
+/// Checks an email address (`addr-spec` of RFC 5322, e.g. `first.last@example.com`).
+///
+/// Non-ASCII domains (e.g. `例子.测试`) are allowed, comments and folding whitespace aren't.
+pub fn is_valid_address(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    local.len() <= 64 && (is_dot_atom(local) || is_quoted_string(local)) && is_domain(domain)
+}
+
+/// Validates a `mailto:` URL (RFC 6068), e.g. `mailto:a@example.com,b@example.com?subject=Hi`.
+///
+/// Every recipient (including those in `to`/`cc`/`bcc` fields) must be a valid address,
+/// and the fields must be `name=value` pairs.
+/// `is_autolink` is for email autolinks, which have no `mailto:` prefix.
+///
+/// Returns the reason if it's invalid.
+pub fn validate_mailto(url: &str, is_autolink: bool) -> Result<(), String> {
+    let rest = match strip_scheme(url, "mailto") {
+        Some(rest) => rest,
+        None if is_autolink => url,
+        None => return Err("it isn't a `mailto:` URL".to_string()),
+    };
+    let (to, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut recipients: Vec<String> = Vec::new();
+    if !to.is_empty() {
+        recipients.extend(to.split(',').map(|address| decode(address).into_owned()));
+    }
+    if !query.is_empty() {
+        for field in query.split('&') {
+            let Some((name, value)) = field.split_once('=') else {
+                return Err(format!("the field `{}` has no value", field));
+            };
+            if name.is_empty() {
+                return Err(format!("the field `{}` has no name", field));
+            }
+            if ["to", "cc", "bcc"].contains(&name.to_lowercase().as_str()) {
+                recipients.extend(
+                    decode(value)
+                        .split(',')
+                        .filter(|address| !address.trim().is_empty())
+                        .map(str::to_string),
+                );
+            }
+        }
+    }
+
+    if recipients.is_empty() {
+        return Err("it has no recipient".to_string());
+    }
+    match recipients
+        .iter()
+        .find(|address| !is_valid_address(address.trim()))
+    {
+        Some(address) => Err(format!("`{}` isn't a valid address", address.trim())),
+        None => Ok(()),
+    }
+}
+
+/// Validates a `tel:` URL: the number must have the E.164 shape,
+/// i.e. `+`, a country code not starting with `0` and at most 15 digits in total
+/// (e.g. `tel:+1-415-555-0100`).
+///
+/// Visual separators (`-`, `.`, `(`, `)` and spaces) and parameters (e.g. `;ext=123`) are allowed.
+///
+/// Returns the reason if it's invalid.
+pub fn validate_tel(url: &str) -> Result<(), String> {
+    let Some(rest) = strip_scheme(url, "tel") else {
+        return Err("it isn't a `tel:` URL".to_string());
+    };
+    let rest = decode(rest);
+    let number = rest.split(';').next().unwrap_or_default();
+    let Some(digits) = number.strip_prefix('+') else {
+        return Err("the number doesn't start with `+` and a country code".to_string());
+    };
+    let mut count = 0;
+    for c in digits.chars() {
+        match c {
+            '0'..='9' => count += 1,
+            '-' | '.' | '(' | ')' | ' ' => {}
+            _ => return Err(format!("`{}` isn't allowed in a phone number", c)),
+        }
+    }
+    let first = digits.chars().find(char::is_ascii_digit);
+    if first.is_none_or(|c| c == '0') {
+        return Err("the country code is missing".to_string());
+    }
+    if count > 15 {
+        return Err("the number has more than 15 digits".to_string());
+    }
+    Ok(())
+}
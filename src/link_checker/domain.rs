@@ -0,0 +1,107 @@
+use super::config::LinkCheckerConfig;
+use super::issue::IssueKind;
+use url::{Host, Url};
+
+/// A list of host patterns, matches if any pattern matches.
+///
+/// A pattern is a host (e.g. `example.com` or `10.0.0.1`), or `*.` and a domain
+/// that matches every subdomain of it (e.g. `*.corp.local` matches `wiki.corp.local`
+/// and `a.b.corp.local`, but not `corp.local` itself). Hosts are matched case-insensitively.
+#[derive(Clone, Debug, Default)]
+pub struct DomainList {
+    patterns: Vec<String>,
+}
+
+impl DomainList {
+    /// Parses the host patterns. Empty patterns are skipped.
+    pub fn parse<'a>(entries: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            patterns: entries
+                .into_iter()
+                .map(normalize_host)
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn is_match(&self, host: &str) -> bool {
+        let host = normalize_host(host);
+        self.patterns
+            .iter()
+            .any(|pattern| match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => *pattern == host,
+            })
+    }
+}
+
+/// Lowercases a host and strips the brackets of IPv6 (e.g. `[::1]`) and the trailing dot.
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    host.trim_end_matches('.').to_lowercase()
+}
+
+/// Checks the host of `url` against `deny_domains` and `allow_domains`.
+///
+/// Returns:
+/// - `IssueKind::DeniedDomain` if the host matches `deny_domains`,
+///   or `allow_domains` is set and the host doesn't match it
+/// - `IssueKind::IpHost` if the host is an IP address (e.g. `http://10.0.0.1/`)
+///   that `allow_domains` doesn't list
+/// - `None` otherwise, or if `url` has no host (e.g. a path or a `mailto:` link)
+pub fn check_host(url: &str, conf: &LinkCheckerConfig) -> Option<IssueKind> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let allowed = conf.allow_domains.is_match(host);
+    if conf.deny_domains.is_match(host) || !(conf.allow_domains.is_empty() || allowed) {
+        return Some(IssueKind::DeniedDomain);
+    }
+    if matches!(url.host(), Some(Host::Ipv4(_) | Host::Ipv6(_))) && !allowed {
+        return Some(IssueKind::IpHost);
+    }
+    None
+}
+
+/// Gets the target of an absolute link to the book itself (at `site_url`),
+/// relative to the root of the book (e.g. `guide/intro.html#install`).
+///
+/// Both `http:` and `https:` links match, whatever the scheme of `site_url` is.
+/// Returns `None` if `site_url` isn't set or `url` points elsewhere.
+pub fn site_target(url: &str, conf: &LinkCheckerConfig) -> Option<String> {
+    let site_url = conf.site_url.as_ref()?;
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https")
+        || url.host_str() != site_url.host_str()
+        || url.port() != site_url.port()
+    {
+        return None;
+    }
+    let site_path = site_url.path();
+    let path = url.path();
+    let rest = match path.strip_prefix(site_path) {
+        Some(rest) => rest,
+        // e.g. `https://example.com/book` for `https://example.com/book/`
+        None if path == site_path.trim_end_matches('/') => "",
+        None => return None,
+    };
+    let mut target = rest.to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    if let Some(fragment) = url.fragment() {
+        target.push('#');
+        target.push_str(fragment);
+    }
+    Some(target)
+}
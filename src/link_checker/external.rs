@@ -0,0 +1,298 @@
+use super::FoundLink;
+use super::config::{ExternalConfig, LinkCheckerConfig};
+use super::issue::{IssueKind, LinkIssue};
+use serde_json::{Map, Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The result of requesting an external URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UrlStatus {
+    /// The server responded with the status code.
+    Status(u16),
+    /// The server can't be reached (e.g. DNS failures, timeouts).
+    Unreachable(String),
+}
+
+impl UrlStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, UrlStatus::Status(code) if *code < 400)
+    }
+}
+
+/// Results of external URLs, kept on the disk between runs.
+///
+/// Only server responses are cached. Unreachable URLs are checked again next time,
+/// because they are usually temporary (e.g. a network failure).
+#[derive(Clone, Debug, Default)]
+pub struct UrlCache {
+    // url -> (checked at, in seconds since UNIX epoch; status code)
+    entries: HashMap<String, (u64, u16)>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl UrlCache {
+    /// Loads the cache from `path`. A missing or invalid file is an empty cache.
+    pub fn load(path: &Path) -> Self {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok())
+            .map(|map| {
+                map.into_iter()
+                    .filter_map(|(url, entry)| {
+                        let checked_at = entry.get("checked_at")?.as_u64()?;
+                        let status = u16::try_from(entry.get("status")?.as_u64()?).ok()?;
+                        Some((url, (checked_at, status)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries }
+    }
+
+    /// Saves the cache to `path`.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let map: Map<String, Value> = self
+            .entries
+            .iter()
+            .map(|(url, (checked_at, status))| {
+                (
+                    url.clone(),
+                    json!({ "checked_at": checked_at, "status": status }),
+                )
+            })
+            .collect();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&map).unwrap_or_default())
+    }
+
+    /// Gets the cached status of `url` if it's not older than `ttl` seconds.
+    pub fn get(&self, url: &str, ttl: u64) -> Option<UrlStatus> {
+        let (checked_at, status) = self.entries.get(url)?;
+        (now_secs().saturating_sub(*checked_at) <= ttl).then_some(UrlStatus::Status(*status))
+    }
+
+    /// Records the status of `url` (unreachable URLs are not recorded).
+    pub fn insert(&mut self, url: &str, status: &UrlStatus) {
+        if let UrlStatus::Status(code) = status {
+            self.entries.insert(url.to_string(), (now_secs(), *code));
+        }
+    }
+}
+
+/// Keeps the requests to the same host apart by at least `interval`.
+struct HostLimiter {
+    interval: Duration,
+    next_slots: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Blocks until a request to `host` is allowed.
+    fn wait(&self, host: &str) {
+        let now = Instant::now();
+        let slot = {
+            let mut next_slots = self.next_slots.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = next_slots.get(host).map_or(now, |&next| next.max(now));
+            next_slots.insert(host.to_string(), slot + self.interval);
+            slot
+        };
+        thread::sleep(slot.saturating_duration_since(now));
+    }
+}
+
+/// Normalizes the URL to request: only `http(s)` URLs are requested, and fragments are dropped.
+fn request_url(url: &str) -> Option<String> {
+    let mut url = url::Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.to_string())
+}
+
+/// Requests `url` once with `method`.
+fn request_once(agent: &ureq::Agent, method: &str, url: &str) -> UrlStatus {
+    match agent.request(method, url).call() {
+        Ok(response) => UrlStatus::Status(response.status()),
+        Err(ureq::Error::Status(code, _)) => UrlStatus::Status(code),
+        Err(ureq::Error::Transport(transport)) => UrlStatus::Unreachable(transport.to_string()),
+    }
+}
+
+/// Whether the request is worth retrying.
+fn should_retry(status: &UrlStatus) -> bool {
+    match status {
+        UrlStatus::Status(code) => *code == 429 || *code >= 500,
+        UrlStatus::Unreachable(_) => true,
+    }
+}
+
+/// Requests `url` with `HEAD` (falling back to `GET` for servers that reject `HEAD`),
+/// retrying temporary failures.
+fn request(
+    agent: &ureq::Agent,
+    limiter: &HostLimiter,
+    url: &str,
+    conf: &ExternalConfig,
+) -> UrlStatus {
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let mut method = "HEAD";
+    let mut attempt = 0;
+    loop {
+        limiter.wait(&host);
+        let status = request_once(agent, method, url);
+        if method == "HEAD" && matches!(status, UrlStatus::Status(code) if code >= 400) {
+            method = "GET";
+            continue;
+        }
+        if attempt >= conf.retries || !should_retry(&status) {
+            return status;
+        }
+        attempt += 1;
+        thread::sleep(Duration::from_millis(500 * u64::from(attempt)));
+    }
+}
+
+/// Requests all `urls` concurrently (at most `conf.concurrency` at a time).
+pub fn request_all(urls: &[String], conf: &ExternalConfig) -> HashMap<String, UrlStatus> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(conf.timeout))
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build();
+    let limiter = HostLimiter::new(Duration::from_millis(conf.host_interval));
+    let queue = Mutex::new(urls.iter());
+    let results = Mutex::new(HashMap::new());
+
+    thread::scope(|scope| {
+        for _ in 0..conf.concurrency.clamp(1, urls.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let Some(url) = queue.lock().unwrap_or_else(|e| e.into_inner()).next() else {
+                        break;
+                    };
+                    let status = request(&agent, &limiter, url, conf);
+                    log::debug!("Checked external URL {}: {:?}", url, status);
+                    results
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(url.clone(), status);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Checks the external (`http(s)`) links by requesting them.
+///
+/// Results are cached in `conf.external.cache_file` (relative to `book_root`).
+/// Links in the `allow_list` are skipped.
+/// `root` is the `src/` directory of the book, used to prompt the issues.
+pub fn check_external_links(
+    links: &[FoundLink],
+    conf: &LinkCheckerConfig,
+    book_root: &Path,
+    root: &Path,
+) -> Vec<LinkIssue> {
+    let external = &conf.external;
+    let cache_path = book_root.join(&external.cache_file);
+    let mut cache = UrlCache::load(&cache_path);
+
+    let mut statuses = HashMap::new();
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    let links: Vec<_> = links
+        .iter()
+        .filter(|link| !conf.is_allowed(&link.url))
+        // Denied hosts (e.g. internal ones) are already prompted, and often unreachable.
+        .filter(|link| super::domain::check_host(&link.url, conf) != Some(IssueKind::DeniedDomain))
+        .collect();
+    for url in links.iter().filter_map(|link| request_url(&link.url)) {
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        match cache.get(&url, external.cache_ttl) {
+            Some(status) => {
+                statuses.insert(url, status);
+            }
+            None => pending.push(url),
+        }
+    }
+
+    log::info!(
+        "Checking {} external URL(s) ({} cached).",
+        pending.len(),
+        statuses.len()
+    );
+    for (url, status) in request_all(&pending, external) {
+        cache.insert(&url, &status);
+        statuses.insert(url, status);
+    }
+    if let Err(e) = cache.save(&cache_path) {
+        log::warn!("Failed to save the external URL cache: {}", e);
+    }
+
+    let mut issues = Vec::new();
+    for link in links {
+        let Some(status) = request_url(&link.url).and_then(|url| statuses.get(&url)) else {
+            continue;
+        };
+        if status.is_ok() {
+            continue;
+        }
+        let (kind, detail) = match status {
+            UrlStatus::Status(code) => (IssueKind::HttpError, format!("HTTP {}", code)),
+            UrlStatus::Unreachable(error) => (IssueKind::UnreachableUrl, error.clone()),
+        };
+        if link.suppressed.contains(&kind) {
+            continue;
+        }
+        let Some(level) = conf.level_of(kind) else {
+            continue;
+        };
+        let issue = LinkIssue {
+            file: link.file.clone(),
+            line: link.line,
+            column: link.column,
+            range: link.range.clone(),
+            kind,
+            url: link.url.clone(),
+            text: link.text.clone(),
+            level,
+            message: kind.message(&link.text, &link.url),
+            suggestion: None,
+            fixable: false,
+        }
+        .with_detail(Some(detail));
+        issue.prompt(root);
+        issues.push(issue);
+    }
+    issues
+}
@@ -0,0 +1,193 @@
+use super::issue::LinkIssue;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A replacement of a byte range in a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// The byte range to replace.
+    pub range: std::ops::Range<usize>,
+    /// The new text of the range.
+    pub replacement: String,
+}
+
+/// The fixes of a file.
+#[derive(Clone, Debug)]
+pub struct FileFix {
+    /// The file (relative to `src/`).
+    pub file: PathBuf,
+    /// The content before fixing.
+    pub original: String,
+    /// The content after fixing.
+    pub fixed: String,
+    /// How many issues are fixed.
+    pub count: usize,
+}
+
+/// Finds where the destination starts in the `source` of a link:
+/// - `[text](dest "title")` and `![alt](dest)`: after the `](` that closes the text
+///   (and the `<` of `<dest>`)
+/// - `<dest>` (autolinks): after the `<`
+/// - Otherwise (e.g. an attribute value in raw HTML): at the start
+///
+/// Returns `None` for links without a destination (e.g. `[text][label]`).
+fn destination_start(source: &str) -> Option<usize> {
+    let text_start = if source.starts_with("![") {
+        2
+    } else if source.starts_with('[') {
+        1
+    } else if source.starts_with('<') {
+        return Some(1);
+    } else {
+        return Some(0);
+    };
+
+    // Finds the `]` that closes the text (brackets inside it are balanced).
+    let mut depth = 1;
+    let mut chars = source[text_start..].char_indices();
+    let text_end = loop {
+        let (i, c) = chars.next()?;
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break text_start + i + 1;
+                }
+            }
+            _ => {}
+        }
+    };
+    let rest = source[text_end..].strip_prefix('(')?;
+    let trimmed = rest.trim_start();
+    let offset = source.len() - trimmed.len();
+    Some(if trimmed.starts_with('<') {
+        offset + 1
+    } else {
+        offset
+    })
+}
+
+/// Finds the edit that fixes `issue` in `content`.
+///
+/// Only issues with a fixable suggestion (see `Suggestion::fixable`) are fixed,
+/// and only inside the range reported for the issue:
+/// the destination of the link must be the URL (so e.g. reference links, whose URL is
+/// in the definition, or destinations with escapes are skipped).
+pub fn edit_for(issue: &LinkIssue, content: &str) -> Option<Edit> {
+    let suggestion = issue.suggestion.as_ref().filter(|_| issue.fixable)?;
+    if issue.url.is_empty() {
+        return None;
+    }
+    let source = content.get(issue.range.clone())?;
+    let start = issue.range.start + destination_start(source)?;
+    if !content[start..].starts_with(issue.url.as_str()) {
+        return None;
+    }
+    Some(Edit {
+        range: start..start + issue.url.len(),
+        replacement: suggestion.clone(),
+    })
+}
+
+/// Applies the `edits` to `content`. Overlapping edits are skipped.
+///
+/// Returns the new content and how many edits are applied.
+pub fn apply_edits(content: &str, mut edits: Vec<Edit>) -> (String, usize) {
+    edits.sort_by_key(|edit| edit.range.start);
+    let mut fixed = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut count = 0;
+    for edit in edits {
+        if edit.range.start < last {
+            continue;
+        }
+        fixed.push_str(&content[last..edit.range.start]);
+        fixed.push_str(&edit.replacement);
+        last = edit.range.end;
+        count += 1;
+    }
+    fixed.push_str(&content[last..]);
+    (fixed, count)
+}
+
+/// Plans the fixes of `issues`, reading the files from `root` (the `src/` of the book).
+pub fn plan_fixes(issues: &[LinkIssue], root: &Path) -> Vec<FileFix> {
+    let mut by_file: BTreeMap<&Path, Vec<&LinkIssue>> = BTreeMap::new();
+    for issue in issues.iter().filter(|issue| issue.fixable) {
+        by_file.entry(&issue.file).or_default().push(issue);
+    }
+
+    let mut fixes = Vec::new();
+    for (file, issues) in by_file {
+        let Ok(original) = fs::read_to_string(root.join(file)) else {
+            log::warn!("Failed to read `{}`, it won't be fixed.", file.display());
+            continue;
+        };
+        let edits = issues
+            .into_iter()
+            .filter_map(|issue| edit_for(issue, &original))
+            .collect();
+        let (fixed, count) = apply_edits(&original, edits);
+        if count > 0 {
+            fixes.push(FileFix {
+                file: file.to_path_buf(),
+                original,
+                fixed,
+                count,
+            });
+        }
+    }
+    fixes
+}
+
+/// Renders a unified diff (with 3 lines of context) between `original` and `fixed`.
+///
+/// Fixes never add or remove lines, so the lines are compared one by one.
+pub fn unified_diff(path: &str, original: &str, fixed: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = fixed.lines().collect();
+    let len = old.len().max(new.len());
+    let changed: Vec<usize> = (0..len).filter(|&i| old.get(i) != new.get(i)).collect();
+
+    let mut diff = format!("--- a/{0}\n+++ b/{0}\n", path);
+    let mut i = 0;
+    while i < changed.len() {
+        // Merge the changes whose contexts overlap into one hunk.
+        let mut j = i;
+        while j + 1 < changed.len() && changed[j + 1] - changed[j] <= CONTEXT * 2 {
+            j += 1;
+        }
+        let start = changed[i].saturating_sub(CONTEXT);
+        let end = (changed[j] + CONTEXT + 1).min(len);
+        let old_count = end.min(old.len()).saturating_sub(start);
+        let new_count = end.min(new.len()).saturating_sub(start);
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            old_count,
+            start + 1,
+            new_count
+        ));
+        for line in start..end {
+            match (old.get(line), new.get(line)) {
+                (Some(a), Some(b)) if a == b => diff.push_str(&format!(" {}\n", a)),
+                (a, b) => {
+                    if let Some(a) = a {
+                        diff.push_str(&format!("-{}\n", a));
+                    }
+                    if let Some(b) = b {
+                        diff.push_str(&format!("+{}\n", b));
+                    }
+                }
+            }
+        }
+        i = j + 1;
+    }
+    diff
+}
@@ -0,0 +1,177 @@
+/// An attribute of an HTML start tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtmlAttribute<'a> {
+    /// The tag name (lowercase).
+    pub tag: String,
+    /// The attribute name (lowercase).
+    pub name: String,
+    /// The attribute value (as written, entities are not decoded).
+    pub value: &'a str,
+    /// The byte offset of the value in the scanned HTML.
+    pub offset: usize,
+}
+
+impl HtmlAttribute<'_> {
+    /// Whether the attribute is a link to another resource (e.g. `<a href>` or `<img src>`).
+    pub fn is_link(&self) -> bool {
+        match self.name.as_str() {
+            "href" => matches!(self.tag.as_str(), "a" | "area" | "link"),
+            "src" => matches!(
+                self.tag.as_str(),
+                "img" | "iframe" | "script" | "source" | "video" | "audio" | "embed" | "track"
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the attribute is an embedded image (e.g. `<img src>`).
+    pub fn is_image(&self) -> bool {
+        self.tag == "img" && self.name == "src"
+    }
+
+    /// Whether the attribute defines an anchor (e.g. `<a id>` or `<a name>`).
+    pub fn is_anchor(&self) -> bool {
+        self.name == "id" || (self.tag == "a" && self.name == "name")
+    }
+}
+
+/// Scans the attributes (with values) of all start tags in `html`.
+///
+/// It's a lightweight scanner rather than a full HTML parser:
+/// comments and end tags are skipped, and entities are not decoded.
+pub fn scan_attributes(html: &str) -> Vec<HtmlAttribute<'_>> {
+    let bytes = html.as_bytes();
+    let mut attributes = Vec::new();
+    let mut pos = 0;
+
+    while let Some(start) = html[pos..].find('<').map(|i| pos + i) {
+        pos = start + 1;
+        if html[pos..].starts_with("!--") {
+            pos = html[pos..].find("-->").map_or(html.len(), |i| pos + i + 3);
+            continue;
+        }
+
+        let tag_end = scan_name(bytes, pos);
+        if tag_end == pos {
+            // End tags, doctypes or a single `<`.
+            continue;
+        }
+        let tag = html[pos..tag_end].to_ascii_lowercase();
+        pos = tag_end;
+
+        loop {
+            pos = skip_whitespace(bytes, pos);
+            match bytes.get(pos) {
+                None => break,
+                Some(b'>') => {
+                    pos += 1;
+                    break;
+                }
+                Some(b'/') => {
+                    pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let name_end = scan_name(bytes, pos);
+            if name_end == pos {
+                // Not an attribute name (e.g. a stray quote), skip it.
+                pos += 1;
+                continue;
+            }
+            let name = html[pos..name_end].to_ascii_lowercase();
+            pos = skip_whitespace(bytes, name_end);
+            if bytes.get(pos) != Some(&b'=') {
+                continue;
+            }
+            pos = skip_whitespace(bytes, pos + 1);
+
+            let (value_start, value_end, next) = match bytes.get(pos) {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let end = html[pos + 1..]
+                        .find(quote as char)
+                        .map_or(html.len(), |i| pos + 1 + i);
+                    (pos + 1, end, (end + 1).min(html.len()))
+                }
+                _ => {
+                    let end = html[pos..]
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .map_or(html.len(), |i| pos + i);
+                    (pos, end, end)
+                }
+            };
+            attributes.push(HtmlAttribute {
+                tag: tag.clone(),
+                name,
+                value: &html[value_start..value_end],
+                offset: value_start,
+            });
+            pos = next;
+        }
+    }
+
+    attributes
+}
+
+/// Scans a tag or attribute name, returns its end.
+fn scan_name(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while let Some(&b) = bytes.get(end) {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'.') {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+fn skip_whitespace(bytes: &[u8], start: usize) -> usize {
+    let mut pos = start;
+    while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+/// HTML collected from several events (e.g. the lines of an HTML block),
+/// with a map from its offsets back to the source.
+#[derive(Clone, Debug, Default)]
+pub struct HtmlChunks {
+    text: String,
+    // (offset in `text`, offset in the source) of every chunk
+    segments: Vec<(usize, usize)>,
+}
+
+impl HtmlChunks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a chunk that starts at `source_offset` in the source.
+    pub fn push(&mut self, chunk: &str, source_offset: usize) {
+        self.segments.push((self.text.len(), source_offset));
+        self.text.push_str(chunk);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Maps an offset in the collected text back to the source.
+    pub fn source_offset(&self, offset: usize) -> usize {
+        let index = self
+            .segments
+            .partition_point(|&(text_offset, _)| text_offset <= offset);
+        match index.checked_sub(1).map(|i| self.segments[i]) {
+            Some((text_offset, source_offset)) => source_offset + (offset - text_offset),
+            None => offset,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.segments.clear();
+    }
+}
@@ -0,0 +1,376 @@
+use super::line_index::LineIndex;
+use super::suggest::Suggestion;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Represents different types of link issues
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// A reference-like link whose reference can't be found.
+    Broken,
+    /// An autolink or email that isn't a valid URL.
+    InvalidSimple,
+    /// A link that is neither a valid URL nor an existing path.
+    InvalidPath,
+    /// A link whose `#fragment` matches no heading of the target.
+    MissingAnchor,
+    /// A reference definition (e.g. `[note]: ./a.md`) that no link uses.
+    UnusedReference,
+    /// An image whose source can't be found.
+    MissingImage,
+    /// An image without alt text.
+    EmptyAlt,
+    /// An external URL that responds with a `4xx`/`5xx` status.
+    HttpError,
+    /// An external URL whose server can't be reached (e.g. DNS failures).
+    UnreachableUrl,
+    /// A link that matches the `black_list`.
+    Blacklisted,
+    /// A markdown file that is neither a chapter nor linked.
+    OrphanPage,
+    /// An asset (e.g. an image) that is never linked.
+    UnusedAsset,
+    /// A path whose case doesn't match the real file name (e.g. `Guide.md` for `guide.md`).
+    CaseMismatch,
+    /// A path with spaces that aren't percent-encoded (e.g. `<./my chapter.md>`).
+    UnencodedSpace,
+    /// A link to a directory without an index chapter (`README.md` or `index.md`).
+    MissingIndex,
+    /// A link that escapes the `src/` of the book (e.g. `../../README.md`).
+    OutsideRoot,
+    /// A link into the `build-dir` or the theme directory.
+    ForbiddenDir,
+    /// A link through a symlink when `follow_symlinks = "never"`.
+    Symlink,
+    /// A `mailto:` link (or an email autolink) with an invalid address.
+    InvalidEmail,
+    /// A `tel:` link that isn't an E.164 phone number.
+    InvalidPhone,
+    /// An email address when `forbid_email` is on.
+    ForbiddenEmail,
+    /// A URL whose scheme isn't in `schemes` (e.g. `file:` or `javascript:`).
+    ForbiddenScheme,
+    /// An `http:` URL, which should be `https:`.
+    InsecureHttp,
+    /// A URL whose host matches `deny_domains` (or isn't in `allow_domains`).
+    DeniedDomain,
+    /// A URL whose host is an IP address.
+    IpHost,
+    /// An absolute URL to the book itself (at `site_url`), which should be relative.
+    SelfLink,
+}
+
+impl IssueKind {
+    /// All kinds, in the order of declaration.
+    pub const ALL: [IssueKind; 26] = [
+        IssueKind::Broken,
+        IssueKind::InvalidSimple,
+        IssueKind::InvalidPath,
+        IssueKind::MissingAnchor,
+        IssueKind::UnusedReference,
+        IssueKind::MissingImage,
+        IssueKind::EmptyAlt,
+        IssueKind::HttpError,
+        IssueKind::UnreachableUrl,
+        IssueKind::Blacklisted,
+        IssueKind::OrphanPage,
+        IssueKind::UnusedAsset,
+        IssueKind::CaseMismatch,
+        IssueKind::UnencodedSpace,
+        IssueKind::MissingIndex,
+        IssueKind::OutsideRoot,
+        IssueKind::ForbiddenDir,
+        IssueKind::Symlink,
+        IssueKind::InvalidEmail,
+        IssueKind::InvalidPhone,
+        IssueKind::ForbiddenEmail,
+        IssueKind::ForbiddenScheme,
+        IssueKind::InsecureHttp,
+        IssueKind::DeniedDomain,
+        IssueKind::IpHost,
+        IssueKind::SelfLink,
+    ];
+
+    /// Finds the kind by its rule name.
+    pub fn from_rule(rule: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.rule() == rule)
+    }
+
+    /// Whether a replacement of the URL can be suggested for the kind.
+    pub fn is_suggestible(&self) -> bool {
+        matches!(
+            self,
+            IssueKind::InvalidPath
+                | IssueKind::MissingImage
+                | IssueKind::MissingAnchor
+                | IssueKind::CaseMismatch
+        )
+    }
+
+    /// The rule name of the kind, used in reports.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            IssueKind::Broken => "broken-reference",
+            IssueKind::InvalidSimple => "invalid-url",
+            IssueKind::InvalidPath => "missing-file",
+            IssueKind::MissingAnchor => "missing-anchor",
+            IssueKind::UnusedReference => "unused-reference",
+            IssueKind::MissingImage => "missing-image",
+            IssueKind::EmptyAlt => "empty-alt",
+            IssueKind::HttpError => "http-error",
+            IssueKind::UnreachableUrl => "unreachable-url",
+            IssueKind::Blacklisted => "blacklisted",
+            IssueKind::OrphanPage => "orphan-page",
+            IssueKind::UnusedAsset => "unused-asset",
+            IssueKind::CaseMismatch => "case-mismatch",
+            IssueKind::UnencodedSpace => "unencoded-space",
+            IssueKind::MissingIndex => "missing-index",
+            IssueKind::OutsideRoot => "outside-root",
+            IssueKind::ForbiddenDir => "forbidden-dir",
+            IssueKind::Symlink => "symlink",
+            IssueKind::InvalidEmail => "invalid-email",
+            IssueKind::InvalidPhone => "invalid-phone",
+            IssueKind::ForbiddenEmail => "forbidden-email",
+            IssueKind::ForbiddenScheme => "forbidden-scheme",
+            IssueKind::InsecureHttp => "insecure-http",
+            IssueKind::DeniedDomain => "denied-domain",
+            IssueKind::IpHost => "ip-host",
+            IssueKind::SelfLink => "self-link",
+        }
+    }
+
+    /// The readable message of an issue of the kind.
+    ///
+    /// `text` is the text of the link (or the label of the definition).
+    pub fn message(&self, text: &str, url: &str) -> String {
+        match self {
+            IssueKind::Broken => format!(
+                "[{}] is a broken URL (or path).\nWarn: The behavior is not yet stable",
+                text
+            ),
+            IssueKind::InvalidSimple => format!("<{}> isn't a valid URL.", text),
+            IssueKind::InvalidPath => format!("[{}]({}) isn't a valid URL (or path).", text, url),
+            IssueKind::MissingAnchor => format!(
+                "[{}]({}) points to an anchor that no heading has.",
+                text, url
+            ),
+            IssueKind::UnusedReference => format!("[{}]: {} is never used.", text, url),
+            IssueKind::MissingImage => format!("![{}]({}) isn't an existing image.", text, url),
+            IssueKind::EmptyAlt => format!("![]({}) has no alt text.", url),
+            IssueKind::HttpError => format!("[{}]({}) responds with an error.", text, url),
+            IssueKind::UnreachableUrl => format!("[{}]({}) can't be reached.", text, url),
+            IssueKind::Blacklisted => format!("[{}]({}) is in the black list.", text, url),
+            IssueKind::OrphanPage => {
+                format!("`{}` isn't in `SUMMARY.md` and no link points to it.", text)
+            }
+            IssueKind::UnusedAsset => format!("`{}` is never used.", text),
+            IssueKind::CaseMismatch => format!(
+                "[{}]({}) doesn't match the case of the real path.",
+                text, url
+            ),
+            IssueKind::UnencodedSpace => format!(
+                "[{}](<{}>) has spaces that may break in the rendered HTML.",
+                text, url
+            ),
+            IssueKind::MissingIndex => format!(
+                "[{}]({}) points to a directory without an index chapter (`README.md` or `index.md`).",
+                text, url
+            ),
+            IssueKind::OutsideRoot => format!(
+                "[{}]({}) points outside the book source, which isn't part of the book.",
+                text, url
+            ),
+            IssueKind::ForbiddenDir => format!(
+                "[{}]({}) points into the build output or the theme directory.",
+                text, url
+            ),
+            IssueKind::Symlink => format!(
+                "[{}]({}) goes through a symlink, which `follow_symlinks` forbids.",
+                text, url
+            ),
+            IssueKind::InvalidEmail => format!("[{}]({}) isn't a valid email link.", text, url),
+            IssueKind::InvalidPhone => format!("[{}]({}) isn't a valid phone link.", text, url),
+            IssueKind::ForbiddenEmail => format!(
+                "[{}]({}) is an email address, which `forbid_email` forbids.",
+                text, url
+            ),
+            IssueKind::ForbiddenScheme => format!(
+                "[{}]({}) has a scheme that `schemes` doesn't allow.",
+                text, url
+            ),
+            IssueKind::InsecureHttp => format!("[{}]({}) uses insecure `http:`.", text, url),
+            IssueKind::DeniedDomain => format!(
+                "[{}]({}) points to a host that the domain lists don't allow.",
+                text, url
+            ),
+            IssueKind::IpHost => format!("[{}]({}) points to an IP address.", text, url),
+            IssueKind::SelfLink => format!(
+                "[{}]({}) is an absolute link to the book itself, which should be relative.",
+                text, url
+            ),
+        }
+    }
+
+    /// A short description of the kind, used in reports.
+    pub fn description(&self) -> &'static str {
+        match self {
+            IssueKind::Broken => "The reference of the link can't be found.",
+            IssueKind::InvalidSimple => "The URL can't be parsed.",
+            IssueKind::InvalidPath => "The link is neither a valid URL nor an existing path.",
+            IssueKind::MissingAnchor => "No heading of the target has the anchor.",
+            IssueKind::UnusedReference => "The reference definition is never used.",
+            IssueKind::MissingImage => {
+                "The image source is neither a valid URL nor an existing file."
+            }
+            IssueKind::EmptyAlt => "The image has no alt text.",
+            IssueKind::HttpError => "The external URL responds with a 4xx/5xx status.",
+            IssueKind::UnreachableUrl => "The server of the external URL can't be reached.",
+            IssueKind::Blacklisted => "The link matches the black list.",
+            IssueKind::OrphanPage => "The page isn't a chapter and no link points to it.",
+            IssueKind::UnusedAsset => "The asset is never used.",
+            IssueKind::CaseMismatch => "The case of the path doesn't match the real file name.",
+            IssueKind::UnencodedSpace => "The path has spaces that aren't percent-encoded.",
+            IssueKind::MissingIndex => "The directory has no index chapter.",
+            IssueKind::OutsideRoot => "The link escapes the source directory of the book.",
+            IssueKind::ForbiddenDir => "The link points into the build-dir or the theme directory.",
+            IssueKind::Symlink => "The link goes through a symlink.",
+            IssueKind::InvalidEmail => "The email link has an invalid address (RFC 5322).",
+            IssueKind::InvalidPhone => "The phone link isn't an E.164 number.",
+            IssueKind::ForbiddenEmail => "Email addresses are forbidden in the book.",
+            IssueKind::ForbiddenScheme => "The scheme of the URL isn't allowed.",
+            IssueKind::InsecureHttp => "The URL uses `http:` instead of `https:`.",
+            IssueKind::DeniedDomain => "The host of the URL isn't allowed.",
+            IssueKind::IpHost => "The host of the URL is an IP address.",
+            IssueKind::SelfLink => "The absolute URL points to the book itself.",
+        }
+    }
+}
+
+/// A link issue found by the link checker.
+#[derive(Clone, Debug)]
+pub struct LinkIssue {
+    /// The file (relative to `src/`) where the link is.
+    pub file: PathBuf,
+    /// The line (1-based) where the link starts.
+    pub line: usize,
+    /// The column (1-based, in characters) where the link starts.
+    pub column: usize,
+    /// The byte range of the link in the file.
+    pub range: std::ops::Range<usize>,
+    /// What's wrong with the link.
+    pub kind: IssueKind,
+    /// The destination of the link.
+    pub url: String,
+    /// The text of the link.
+    pub text: String,
+    /// The level that the issue is prompted at.
+    pub level: log::Level,
+    /// The readable message of the issue.
+    pub message: String,
+    /// A replacement of the URL that probably fixes the issue (e.g. `./intro.md` for `./Intro.md`).
+    pub suggestion: Option<String>,
+    /// Whether `--fix` may apply the suggestion (see `Suggestion::fixable`).
+    pub fixable: bool,
+}
+
+impl LinkIssue {
+    /// Creates an issue of `kind`, located by `lines`.
+    pub fn new(
+        kind: IssueKind,
+        file: &Path,
+        lines: &LineIndex,
+        range: std::ops::Range<usize>,
+        url: &str,
+        text: &str,
+        level: log::Level,
+    ) -> Self {
+        let (line, column) = lines.position(range.start);
+        Self {
+            file: file.to_path_buf(),
+            line,
+            column,
+            range,
+            kind,
+            url: url.to_string(),
+            text: text.to_string(),
+            level,
+            message: kind.message(text, url),
+            suggestion: None,
+            fixable: false,
+        }
+    }
+
+    /// Appends the detail (e.g. why the link is invalid) to the message.
+    pub fn with_detail(mut self, detail: Option<String>) -> Self {
+        if let Some(detail) = detail {
+            self.message.push_str(&format!(" ({})", detail));
+        }
+        self
+    }
+
+    /// Sets the suggestion, which is also appended to the message.
+    pub fn with_suggestion(mut self, suggestion: Option<Suggestion>) -> Self {
+        if let Some(suggestion) = suggestion {
+            self.message
+                .push_str(&format!(" Did you mean `{}`?", suggestion.link));
+            self.suggestion = Some(suggestion.link);
+            self.fixable = suggestion.fixable;
+        }
+        self
+    }
+
+    /// The rule name of the issue (the same as `IssueKind::rule`).
+    pub fn rule(&self) -> &'static str {
+        self.kind.rule()
+    }
+
+    /// Logs the issue at its level.
+    ///
+    /// `root` is the `src/` directory of the book (see `location`).
+    pub fn prompt(&self, root: &Path) {
+        log::log!(
+            self.level,
+            "{}: [{}] {}",
+            self.location(root),
+            self.rule(),
+            self.message
+        );
+    }
+
+    /// Formats the location as `path:line:col`, which terminals and editors can open.
+    ///
+    /// `root` is the `src/` directory of the book, the path is relative to
+    /// the current directory when possible (otherwise absolute).
+    pub fn location(&self, root: &Path) -> String {
+        let full_path = root.join(&self.file);
+        let path = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| full_path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+            .unwrap_or(full_path);
+        format!("{}:{}:{}", path.display(), self.line, self.column)
+    }
+}
+
+impl fmt::Display for LinkIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: [{}] {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.rule(),
+            self.message
+        )
+    }
+}
+
+/// Summarizes the issues into a readable text (one issue per line).
+pub fn summarize(issues: &[LinkIssue]) -> String {
+    let mut summary = format!("{} link issue(s) found:", issues.len());
+    for issue in issues {
+        summary.push('\n');
+        summary.push_str(&issue.to_string());
+    }
+    summary
+}
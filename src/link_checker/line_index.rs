@@ -0,0 +1,34 @@
+/// Maps byte offsets of a text to 1-based line and column numbers.
+///
+/// Columns are counted in characters (not bytes),
+/// so that a CJK character counts as one column, the same as editors do.
+#[derive(Clone, Debug)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Indexes the start of every line of `text`.
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    /// Gets the line and column (both 1-based) of a byte offset.
+    ///
+    /// Offsets beyond the text are clamped to its end,
+    /// and offsets inside a character are moved to its start.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.text[line_start..offset].chars().count() + 1;
+        (line, column)
+    }
+}
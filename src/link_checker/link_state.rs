@@ -1,30 +1,30 @@
+use super::FoundLink;
+use super::anchors::AnchorIndex;
 use super::config::LinkCheckerConfig;
-use log;
+use super::contact::{self, ContactKind};
+use super::domain;
+use super::issue::{IssueKind, LinkIssue};
+use super::line_index::LineIndex;
+use super::path_checker::TargetState;
+use super::suggest::{Suggestion, relative_link, suggest_link};
 use pulldown_cmark::{CowStr, LinkType};
 use std::path::Path;
 
-/// Tracks the state of a link being processed
+/// Tracks the state of a link (or an image) being processed
 #[derive(Clone, Debug)]
 pub struct LinkState<'a> {
     active: bool,
+    is_image: bool,
     text: String,
     url: CowStr<'a>,
     link_type: LinkType,
 }
 
-/// Represents different types of link issues
-#[derive(Debug)]
-enum LinkIssue {
-    Broken,
-    InvalidSimple,
-    InvalidPath,
-    Valid,
-}
-
 impl<'a> LinkState<'a> {
     pub fn new() -> Self {
         Self {
             active: false,
+            is_image: false,
             text: String::with_capacity(128),
             url: CowStr::Borrowed(""),
             link_type: LinkType::Inline,
@@ -33,11 +33,18 @@ impl<'a> LinkState<'a> {
 
     pub fn start_link(&mut self, url: CowStr<'a>, link_type: LinkType) {
         self.active = true;
+        self.is_image = false;
         self.url = url;
         self.link_type = link_type;
         self.text.clear();
     }
 
+    /// Starts an image, its text is the alt text.
+    pub fn start_image(&mut self, url: CowStr<'a>, link_type: LinkType) {
+        self.start_link(url, link_type);
+        self.is_image = true;
+    }
+
     pub fn append_text(&mut self, text: &str) {
         self.text.push_str(text);
     }
@@ -62,106 +69,233 @@ impl<'a> LinkState<'a> {
         self.active
             && (matches!(
                 self.link_type,
-                LinkType::Inline | LinkType::Autolink | LinkType::Email
+                LinkType::Inline
+                    | LinkType::Reference
+                    | LinkType::Collapsed
+                    | LinkType::Shortcut
+                    | LinkType::Autolink
+                    | LinkType::Email
             ) || self.is_broken())
     }
 
+    /// Whether the link is a path with spaces that aren't percent-encoded.
+    /// They work in markdown (e.g. `<./my chapter.md>`), but may break in the rendered HTML.
+    pub fn has_unencoded_space(&self) -> bool {
+        self.active
+            && !self.is_broken()
+            && !self.is_simple()
+            && self.url.contains(' ')
+            && !super::path_checker::check_url(&self.url)
+    }
+
+    /// Records the current link, located by `lines`.
+    pub fn found_link(
+        &self,
+        file_path: &Path,
+        lines: &LineIndex,
+        range: std::ops::Range<usize>,
+    ) -> FoundLink {
+        let (line, column) = lines.position(range.start);
+        FoundLink {
+            file: file_path.to_path_buf(),
+            range,
+            line,
+            column,
+            url: self.url.to_string(),
+            text: self.text.clone(),
+            suppressed: Vec::new(),
+        }
+    }
+
     pub fn reset(&mut self) {
         self.active = false;
         self.text.clear();
         self.url = CowStr::Borrowed("");
     }
 
+    /// Checks the scheme of the link against `schemes`.
+    /// Email autolinks have the `mailto` scheme, paths have no scheme and are never prompted here.
+    fn scheme_issue(&self, conf: &LinkCheckerConfig) -> Option<IssueKind> {
+        if !self.active || self.is_broken() {
+            return None;
+        }
+        let scheme = match self.link_type {
+            LinkType::Email => "mailto".to_string(),
+            _ => super::path_checker::url_scheme(&self.url)?,
+        };
+        match scheme.as_str() {
+            _ if !conf.schemes.contains(&scheme) => Some(IssueKind::ForbiddenScheme),
+            "http" => Some(IssueKind::InsecureHttp),
+            _ => None,
+        }
+    }
+
+    /// The link with the `https` scheme, e.g. `https://example.com/` for ` HTTP://example.com/`.
+    /// Returns `None` if the scheme isn't `http` (case-insensitive).
+    fn https_url(&self) -> Option<String> {
+        let url = self.url.trim();
+        let scheme = url.get(..5)?;
+        scheme
+            .eq_ignore_ascii_case("http:")
+            .then(|| format!("https:{}", &url[5..]))
+    }
+
+    /// Checks the host of the link against the domain lists and `site_url`.
+    fn host_issues(&self, conf: &LinkCheckerConfig) -> impl Iterator<Item = IssueKind> {
+        let checked = self.active && !self.is_broken() && self.link_type != LinkType::Email;
+        let host_issue = checked
+            .then(|| domain::check_host(&self.url, conf))
+            .flatten();
+        let self_link = (checked && domain::site_target(&self.url, conf).is_some())
+            .then_some(IssueKind::SelfLink);
+        host_issue.into_iter().chain(self_link)
+    }
+
+    /// The kind of the link if it's a contact link (`mailto:`, `tel:` or an email autolink).
+    fn contact_kind(&self) -> Option<ContactKind> {
+        if !self.active || self.is_broken() {
+            return None;
+        }
+        if self.link_type == LinkType::Email {
+            return Some(ContactKind::Email);
+        }
+        contact::contact_kind(&self.url)
+    }
+
+    /// Checks a contact link, returns the kind of the issue and the reason (if any).
+    /// The `black_list` is checked first, like `check_path` does for other links.
+    fn check_contact(
+        &self,
+        contact: ContactKind,
+        conf: &LinkCheckerConfig,
+    ) -> Option<(IssueKind, Option<String>)> {
+        if conf.black_list.is_match(&self.url) {
+            return Some((IssueKind::Blacklisted, None));
+        }
+        let result = match contact {
+            ContactKind::Email if conf.forbid_email => {
+                return Some((IssueKind::ForbiddenEmail, None));
+            }
+            ContactKind::Email => {
+                contact::validate_mailto(&self.url, self.link_type == LinkType::Email)
+                    .map_err(|reason| (IssueKind::InvalidEmail, reason))
+            }
+            ContactKind::Phone => {
+                contact::validate_tel(&self.url).map_err(|reason| (IssueKind::InvalidPhone, reason))
+            }
+        };
+        result.err().map(|(kind, reason)| (kind, Some(reason)))
+    }
+
     /// Determine what kind of issue the link has (if any)
-    fn classify_issue(&self, file_path: &Path, root: &Path, conf: &LinkCheckerConfig) -> LinkIssue {
+    fn classify_issue(
+        &self,
+        file_path: &Path,
+        root: &Path,
+        index: &AnchorIndex,
+        conf: &LinkCheckerConfig,
+    ) -> Option<IssueKind> {
         if !self.active {
-            return LinkIssue::Valid;
+            return None;
         }
 
         match () {
-            _ if self.is_broken() => LinkIssue::Broken,
+            _ if self.is_broken() => Some(IssueKind::Broken),
             _ if self.is_simple() => {
                 if !super::path_checker::check_url(&self.url) {
-                    LinkIssue::InvalidSimple
+                    Some(IssueKind::InvalidSimple)
                 } else {
-                    LinkIssue::Valid
+                    None
                 }
             }
-            _ if !super::path_checker::check_path(&self.url, file_path, root, conf) => {
-                LinkIssue::InvalidPath
-            }
-            _ => LinkIssue::Valid,
+            _ => match super::path_checker::check_path(&self.url, file_path, root, index, conf) {
+                TargetState::Valid => None,
+                TargetState::Missing if self.is_image => Some(IssueKind::MissingImage),
+                TargetState::Missing => Some(IssueKind::InvalidPath),
+                TargetState::MissingAnchor => Some(IssueKind::MissingAnchor),
+                TargetState::Blacklisted => Some(IssueKind::Blacklisted),
+                TargetState::CaseMismatch => Some(IssueKind::CaseMismatch),
+                TargetState::MissingIndex => Some(IssueKind::MissingIndex),
+                TargetState::OutsideRoot => Some(IssueKind::OutsideRoot),
+                TargetState::ForbiddenDir => Some(IssueKind::ForbiddenDir),
+                TargetState::Symlink => Some(IssueKind::Symlink),
+            },
         }
     }
 
-    pub fn check_and_prompt(
+    /// Checks the link and resets the state.
+    /// Links in the `allow_list` are skipped.
+    ///
+    /// `lines` is the line index of the file, used to locate the link.
+    /// The issues are **not** prompted here, because they may be suppressed later.
+    pub fn check(
         &mut self,
         file_path: &Path,
+        lines: &LineIndex,
         range: std::ops::Range<usize>,
         root: &Path,
+        index: &AnchorIndex,
         conf: &LinkCheckerConfig,
-    ) -> bool {
-        let issue = self.classify_issue(file_path, root, conf);
-        let prompt_level = conf.prompt_level;
+    ) -> Vec<LinkIssue> {
+        if conf.is_allowed(&self.url) {
+            self.reset();
+            return Vec::new();
+        }
 
-        let has_issue = match issue {
-            LinkIssue::Broken => {
-                self.log_issue(
-                    file_path,
-                    range,
-                    prompt_level,
-                    "broken",
-                    &format!(
-                        "[{}] is a broken URL (or path).\nWarn: The behavior is not yet stable",
-                        self.text
-                    ),
-                );
-                true
-            }
-            LinkIssue::InvalidSimple => {
-                self.log_issue(
-                    file_path,
-                    range,
-                    prompt_level,
-                    "invalid",
-                    &format!("<{}> isn't a valid URL.", self.text),
-                );
-                true
+        // (kind, detail)
+        let mut kinds = Vec::new();
+        let scheme_issue = self.scheme_issue(conf);
+        if let Some(kind) = scheme_issue {
+            kinds.push((kind, None));
+        }
+        if scheme_issue != Some(IssueKind::ForbiddenScheme) {
+            kinds.extend(self.host_issues(conf).map(|kind| (kind, None)));
+            match self.contact_kind() {
+                Some(contact) => kinds.extend(self.check_contact(contact, conf)),
+                None => kinds.extend(
+                    self.classify_issue(file_path, root, index, conf)
+                        .map(|kind| (kind, None)),
+                ),
             }
-            LinkIssue::InvalidPath => {
-                self.log_issue(
+        }
+        if self.has_unencoded_space() {
+            kinds.push((IssueKind::UnencodedSpace, None));
+        }
+        if self.is_image && conf.warn_empty_alt && self.text.trim().is_empty() {
+            kinds.push((IssueKind::EmptyAlt, None));
+        }
+
+        let issues = kinds
+            .into_iter()
+            .filter_map(|(kind, detail)| Some((kind, detail, conf.level_of(kind)?)))
+            .map(|(kind, detail, level)| {
+                // Only `suggest_link` knows which suggestions are fixable, the others are hints.
+                let suggestion = match kind {
+                    IssueKind::UnencodedSpace => {
+                        Some(Suggestion::hint(self.url.replace(' ', "%20")))
+                    }
+                    IssueKind::InsecureHttp => self.https_url().map(Suggestion::hint),
+                    IssueKind::SelfLink => domain::site_target(&self.url, conf)
+                        .map(|target| Suggestion::hint(relative_link(&target, file_path, root))),
+                    _ if kind.is_suggestible() => suggest_link(&self.url, file_path, root, index),
+                    _ => None,
+                };
+                LinkIssue::new(
+                    kind,
                     file_path,
-                    range,
-                    prompt_level,
-                    "invalid",
-                    &format!("[{}]({}) isn't a valid URL (or path).", self.text, self.url),
-                );
-                true
-            }
-            LinkIssue::Valid => false,
-        };
+                    lines,
+                    range.clone(),
+                    &self.url,
+                    &self.text,
+                    level,
+                )
+                .with_detail(detail)
+                .with_suggestion(suggestion)
+            })
+            .collect();
 
         self.reset();
-        has_issue
-    }
-
-    /// Helper method to log issues with consistent formatting
-    fn log_issue(
-        &self,
-        file_path: &Path,
-        range: std::ops::Range<usize>,
-        level: log::Level,
-        issue_type: &str,
-        message: &str,
-    ) {
-        log::log!(
-            level,
-            "[{}][{}][{}] {}",
-            file_path.display(),
-            super::format_range(&range),
-            issue_type,
-            message
-        );
+        issues
     }
 }
 
@@ -1,61 +1,254 @@
-use pulldown_cmark::{Event, Tag, TagEnd};
+use pulldown_cmark::{Event, LinkType, Tag, TagEnd};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+pub mod anchors;
 pub mod config;
+pub mod contact;
+pub mod domain;
+pub mod external;
+pub mod fixer;
+pub mod html_scanner;
+pub mod issue;
+pub mod line_index;
 pub mod link_state;
+pub mod orphans;
 pub mod path_checker;
+pub mod pattern;
+pub mod report;
+pub mod suggest;
+pub mod suppression;
 
+use anchors::AnchorIndex;
 use config::LinkCheckerConfig;
+use html_scanner::HtmlChunks;
+use issue::{IssueKind, LinkIssue};
+use line_index::LineIndex;
 use link_state::LinkState;
+use suppression::Suppressions;
 
-/// Formats a range for display purposes
-pub fn format_range(range: &std::ops::Range<usize>) -> String {
-    if range.is_empty() {
-        range.start.to_string()
-    } else {
-        format!("{}..{}", range.start, range.end)
+/// A link found by `check_link`, kept for the book-wide checks (e.g. external URLs).
+#[derive(Clone, Debug)]
+pub struct FoundLink {
+    /// The file (relative to `src/`) where the link is.
+    pub file: PathBuf,
+    /// The byte range of the link in the file.
+    pub range: std::ops::Range<usize>,
+    /// The line (1-based) where the link starts.
+    pub line: usize,
+    /// The column (1-based, in characters) where the link starts.
+    pub column: usize,
+    /// The destination of the link.
+    pub url: String,
+    /// The text of the link.
+    pub text: String,
+    /// The rules suppressed for the link by directives (e.g. `<!-- betterlink-disable-line -->`).
+    pub suppressed: Vec<IssueKind>,
+}
+
+/// Normalizes a reference label the way CommonMark matches them:
+/// case-insensitive, with inner whitespace collapsed.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Gets the source offset of an HTML event.
+/// The range of the event may contain some markdown prefix (e.g. `> ` in blockquotes).
+fn html_offset(content: &str, range: &std::ops::Range<usize>, html: &str) -> usize {
+    content
+        .get(range.clone())
+        .and_then(|source| source.find(html))
+        .map_or(range.start, |i| range.start + i)
+}
+
+/// Whether the link (or image) refers to a reference definition.
+fn is_reference(link_type: LinkType) -> bool {
+    matches!(
+        link_type,
+        LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+    )
+}
+
+/// Checks the links (`href`/`src` attributes) in raw HTML.
+fn check_html(
+    chunks: &HtmlChunks,
+    file_path: &Path,
+    lines: &LineIndex,
+    root: &Path,
+    index: &AnchorIndex,
+    conf: &LinkCheckerConfig,
+    found: &mut Vec<FoundLink>,
+) -> Vec<LinkIssue> {
+    let mut issues = Vec::new();
+    for attribute in html_scanner::scan_attributes(chunks.text()) {
+        if !attribute.is_link() {
+            continue;
+        }
+        let start = chunks.source_offset(attribute.offset);
+        let range = start..start + attribute.value.len();
+
+        let mut state = LinkState::new();
+        if attribute.is_image() {
+            state.start_image(attribute.value.into(), LinkType::Inline);
+        } else {
+            state.start_link(attribute.value.into(), LinkType::Inline);
+        }
+        state.append_text(&format!("<{} {}>", attribute.tag, attribute.name));
+        found.push(state.found_link(file_path, lines, range.clone()));
+        issues.extend(state.check(file_path, lines, range, root, index, conf));
     }
+    issues
 }
 
-/// Checks markdown content for invalid links.
+/// Checks markdown content for invalid links (and images).
 ///
 /// # Arguments
 /// * `content` - The markdown content to check
 /// * `path` - Optional path to the markdown file (used for relative path resolution)
 /// * `root` - Root directory that all links must be contained within (It's the `src/` of the current book)
+/// * `index` - Anchors of the whole book (used for `#title` fragments)
 /// * `conf` - Configuration for link checking
+/// * `found` - All checked links are appended to it (for the book-wide checks)
+///
+/// # Returns
+/// All issues found in the content.
 ///
 /// # Notes
 /// - Returns early if path is None
 /// - Logs issues according to the configured prompt_level
-pub fn check_link(content: &str, path: &Option<PathBuf>, root: &Path, conf: &LinkCheckerConfig) {
+/// - Reference definitions that no link uses are prompted (by default at most at `Level::Warn`)
+/// - Issues suppressed by the directives (see `Suppressions`) are dropped
+pub fn check_link(
+    content: &str,
+    path: &Option<PathBuf>,
+    root: &Path,
+    index: &AnchorIndex,
+    conf: &LinkCheckerConfig,
+    found: &mut Vec<FoundLink>,
+) -> Vec<LinkIssue> {
     // Early return if path is None to avoid unnecessary processing
     let Some(file_path) = path else {
-        return;
+        return Vec::new();
     };
 
     let events =
         pulldown_cmark::Parser::new_ext(content, crate::attributes::DEFAULT_PARSER_OPTIONS);
+    let definitions: Vec<(String, String, std::ops::Range<usize>)> = events
+        .reference_definitions()
+        .iter()
+        .map(|(label, def)| (label.to_string(), def.dest.to_string(), def.span.clone()))
+        .collect();
+    let mut used_labels = HashSet::new();
+    let lines = LineIndex::new(content);
     let mut link_state = LinkState::new();
+    // Images can be inside links (e.g. `[![alt](a.png)](./a.md)`), so they have their own state.
+    let mut image_state = LinkState::new();
+    // The lines of an HTML block come one by one, they're checked together at the end of the block.
+    let mut html_block = HtmlChunks::new();
+    let mut suppressions = Suppressions::new();
+    let found_start = found.len();
+    let mut issues = Vec::new();
 
     for (event, range) in events.into_offset_iter() {
         match event {
             Event::Start(Tag::Link {
                 link_type,
                 dest_url,
+                id,
                 ..
             }) => {
+                if is_reference(link_type) {
+                    used_labels.insert(normalize_label(&id));
+                }
                 link_state.start_link(dest_url, link_type);
             }
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                id,
+                ..
+            }) => {
+                if is_reference(link_type) {
+                    used_labels.insert(normalize_label(&id));
+                }
+                image_state.start_image(dest_url, link_type);
+            }
+            Event::Html(html) => {
+                let offset = html_offset(content, &range, &html);
+                suppressions.scan(&html, offset, lines.position(offset).0);
+                html_block.push(&html, offset);
+            }
+            Event::End(TagEnd::HtmlBlock) => {
+                issues.extend(check_html(
+                    &html_block,
+                    file_path,
+                    &lines,
+                    root,
+                    index,
+                    conf,
+                    found,
+                ));
+                html_block.clear();
+            }
+            Event::InlineHtml(html) => {
+                let offset = html_offset(content, &range, &html);
+                suppressions.scan(&html, offset, lines.position(offset).0);
+                let mut chunks = HtmlChunks::new();
+                chunks.push(&html, offset);
+                issues.extend(check_html(
+                    &chunks, file_path, &lines, root, index, conf, found,
+                ));
+            }
+            Event::Text(text) if image_state.is_active() => {
+                image_state.append_text(&text);
+            }
             Event::Text(text) if link_state.is_active() => {
                 link_state.append_text(&text);
             }
+            Event::End(TagEnd::Image) if image_state.should_check() => {
+                found.push(image_state.found_link(file_path, &lines, range.clone()));
+                issues.extend(image_state.check(file_path, &lines, range, root, index, conf));
+            }
             Event::End(TagEnd::Link) if link_state.should_check() => {
-                if link_state.should_check() {
-                    link_state.check_and_prompt(file_path, range, root, conf);
-                }
+                found.push(link_state.found_link(file_path, &lines, range.clone()));
+                issues.extend(link_state.check(file_path, &lines, range, root, index, conf));
             }
             _ => {}
         }
     }
+
+    for (label, dest, span) in definitions {
+        if used_labels.contains(&normalize_label(&label)) || conf.is_allowed(&dest) {
+            continue;
+        }
+        let Some(level) = conf.level_of(IssueKind::UnusedReference) else {
+            continue;
+        };
+        issues.push(LinkIssue::new(
+            IssueKind::UnusedReference,
+            file_path,
+            &lines,
+            span,
+            &dest,
+            &label,
+            level,
+        ));
+    }
+
+    issues.retain(|issue| !suppressions.suppresses(issue));
+    for issue in &issues {
+        issue.prompt(root);
+    }
+    for link in &mut found[found_start..] {
+        link.suppressed = IssueKind::ALL
+            .into_iter()
+            .filter(|&kind| suppressions.is_suppressed(kind, link.line, link.range.start))
+            .collect();
+    }
+
+    issues
 }
@@ -0,0 +1,174 @@
+use super::FoundLink;
+use super::config::{LinkCheckerConfig, SymlinkPolicy};
+use super::issue::{IssueKind, LinkIssue};
+use super::path_checker::{INDEX_NAMES, check_url, decode, resolve_path, split_target};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// The `{{#include}}`-like directives of mdbook.
+/// Escaped directives (e.g. `\{{#include a.md}}`) match without a target, so they are skipped.
+static INCLUDE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\\\{\{#.*\}\}|\{\{\s*#(include|rustdoc_include|playground|playpen)\s+([^}]+)\}\}")
+        .expect("the include regex is valid")
+});
+
+/// The extensions of the files treated as assets (images and other media).
+pub const ASSET_EXTENSIONS: [&str; 12] = [
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "bmp", "ico", "mp4", "webm", "pdf",
+];
+
+/// Collects all files under `dir` (relative to `root`), skipping hidden entries and symlinks.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        // Symlinks are skipped, the files they point to are collected by their real paths.
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_symlink || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+}
+
+/// Resolves the file that a found link points to (relative to `root`), if it's a local file.
+fn resolve_link(link: &FoundLink, root: &Path) -> Option<PathBuf> {
+    if check_url(&link.url) {
+        return None;
+    }
+    let (path_part, _) = split_target(&link.url);
+    let path_part = decode(path_part);
+    let path_part = path_part.as_ref();
+    if path_part.is_empty() {
+        return None;
+    }
+    // Files are collected by their real paths, so the symlinks are followed here.
+    let mut full_path = resolve_path(path_part, &link.file, root, SymlinkPolicy::InsideRoot)?;
+    if !full_path.exists() {
+        return None;
+    }
+    if full_path.is_dir() {
+        full_path = INDEX_NAMES
+            .iter()
+            .map(|name| full_path.join(name))
+            .find(|path| path.is_file())?;
+    }
+    full_path.strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+/// Collects the files that `file` (relative to `root`) pulls in with `{{#include}}`,
+/// `{{#rustdoc_include}}` or `{{#playground}}`, and the files that those pull in.
+///
+/// Like mdbook, the paths are relative to the directory of the including file,
+/// and the `:anchor` or `:lines` after them are ignored.
+fn collect_included(file: &Path, root: &Path, included: &mut HashSet<PathBuf>) {
+    let Ok(content) = fs::read_to_string(root.join(file)) else {
+        return;
+    };
+    let Some(dir) = file.parent() else {
+        return;
+    };
+    for captures in INCLUDE.captures_iter(&content) {
+        let Some(target) = captures.get(2) else {
+            continue;
+        };
+        let Some(path) = target.as_str().split_whitespace().next() else {
+            continue;
+        };
+        let path = path.split(':').next().unwrap_or(path);
+        let Some(path) = root
+            .join(dir)
+            .join(path)
+            .canonicalize()
+            .ok()
+            .and_then(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        else {
+            continue;
+        };
+        if included.insert(path.clone()) {
+            collect_included(&path, root, included);
+        }
+    }
+}
+
+fn file_issue(kind: IssueKind, file: &Path, level: log::Level) -> LinkIssue {
+    let path = file.to_string_lossy().replace('\\', "/");
+    LinkIssue {
+        file: file.to_path_buf(),
+        line: 1,
+        column: 1,
+        range: 0..0,
+        kind,
+        url: String::new(),
+        text: path.clone(),
+        level,
+        message: kind.message(&path, ""),
+        suggestion: None,
+        fixable: false,
+    }
+}
+
+/// Finds the markdown files under `root` that are neither chapters nor linked (orphan pages),
+/// and the assets that are never linked (unused assets).
+///
+/// `chapters` are the source paths of the chapters in `SUMMARY.md` (relative to `root`),
+/// `found` are the links found by `check_link` in the whole book.
+/// The files that chapters pull in with `{{#include}}` (and the like) are referenced too.
+/// Files matching `orphan_ignore` are skipped.
+pub fn check_orphans<'a>(
+    chapters: impl IntoIterator<Item = &'a Path>,
+    found: &[FoundLink],
+    root: &Path,
+    conf: &LinkCheckerConfig,
+) -> Vec<LinkIssue> {
+    let mut referenced: HashSet<PathBuf> = chapters.into_iter().map(Path::to_path_buf).collect();
+    let mut included = HashSet::new();
+    for chapter in &referenced {
+        collect_included(chapter, root, &mut included);
+    }
+    referenced.extend(included);
+    referenced.insert(PathBuf::from("SUMMARY.md"));
+    referenced.extend(found.iter().filter_map(|link| resolve_link(link, root)));
+
+    let mut files = Vec::new();
+    collect_files(root, root, &mut files);
+    files.sort();
+
+    let mut issues = Vec::new();
+    for file in files {
+        if referenced.contains(&file) {
+            continue;
+        }
+        let path = file.to_string_lossy().replace('\\', "/");
+        if conf.orphan_ignore.is_match(&path) {
+            continue;
+        }
+        let extension = file
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let kind = if extension == "md" {
+            IssueKind::OrphanPage
+        } else if ASSET_EXTENSIONS.contains(&extension.as_str()) {
+            IssueKind::UnusedAsset
+        } else {
+            continue;
+        };
+        let Some(level) = conf.level_of(kind) else {
+            continue;
+        };
+        let issue = file_issue(kind, &file, level);
+        issue.prompt(root);
+        issues.push(issue);
+    }
+    issues
+}
@@ -1,26 +1,90 @@
-use std::path::Path;
+use super::anchors::AnchorIndex;
+use super::config::SymlinkPolicy;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The state of a link target after checking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetState {
+    /// The target exists (and so does the anchor, if any).
+    Valid,
+    /// The target doesn't exist (or isn't allowed).
+    Missing,
+    /// The target exists, but no heading in it has the anchor.
+    MissingAnchor,
+    /// The link matches the `black_list`.
+    Blacklisted,
+    /// The target exists, but only when the case of the path is ignored.
+    CaseMismatch,
+    /// The target is a directory without an index chapter (`README.md` or `index.md`).
+    MissingIndex,
+    /// The target is outside the root directory (the `src/` of the book).
+    OutsideRoot,
+    /// The target is inside a directory that links must not point into
+    /// (the `build-dir` or the theme directory).
+    ForbiddenDir,
+    /// The path goes through a symlink, which the `follow_symlinks` policy forbids.
+    Symlink,
+}
 
 /// Checks if a URL/path is valid.
 ///
-/// Returns true if:
+/// Returns `TargetState::Valid` if:
 /// - The URL is a valid absolute URL, or
-/// - The path exists as a file within the root directory
+/// - The path exists as a file within the root directory (and has the anchor, if any)
+///
+/// Links into `forbidden_dirs` (e.g. the `build-dir`) are rejected first.
+///
+/// Anchors are looked up in `index` (files that aren't in the index are read from the disk).
+///
+/// With `strict_case`, the case of every path component must match the real file name,
+/// even on case-insensitive filesystems.
 pub fn check_path(
+    url: &str,
+    path: &Path,
+    root: &Path,
+    index: &AnchorIndex,
+    conf: &super::config::LinkCheckerConfig,
+) -> TargetState {
+    if conf.black_list.is_match(url) {
+        return TargetState::Blacklisted;
+    }
+    if check_url(url) {
+        return TargetState::Valid;
+    }
+    if points_into_forbidden_dir(url, path, root, conf) {
+        return TargetState::ForbiddenDir;
+    }
+    if conf.strict_case && target_case_matches(split_target(url).0, path, root) == Some(false) {
+        return TargetState::CaseMismatch;
+    }
+    is_valid_link_target(url, path, root, index, conf.follow_symlinks).unwrap_or_else(|| {
+        log::warn!("The path checker has an internal error that may cause the decision to fail.");
+        TargetState::Missing
+    })
+}
+
+/// Whether the link points into one of the `forbidden_dirs` (e.g. the `build-dir`).
+fn points_into_forbidden_dir(
     url: &str,
     path: &Path,
     root: &Path,
     conf: &super::config::LinkCheckerConfig,
 ) -> bool {
-    if conf.black_list.contains(url) {
+    if conf.forbidden_dirs.is_empty() {
         return false;
     }
-    check_url(url)
-        || is_valid_link_target(url, path, root).unwrap_or_else(|| {
-            log::warn!(
-                "The path checker has an internal error that may cause the decision to fail."
-            );
-            false
-        })
+    let path_part = decode(split_target(url).0);
+    if path_part.is_empty() {
+        return false;
+    }
+    resolve_path(&path_part, path, root, conf.follow_symlinks).is_some_and(|full_path| {
+        conf.forbidden_dirs
+            .iter()
+            .any(|dir| full_path.starts_with(dir))
+    })
 }
 
 /// Checks if a string is a valid URL
@@ -28,6 +92,152 @@ pub fn check_url(path: &str) -> bool {
     url::Url::parse(path).is_ok()
 }
 
+/// Gets the scheme (lowercase) of a URL, `None` if it isn't a valid URL (e.g. a path).
+pub fn url_scheme(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .map(|url| url.scheme().to_string())
+}
+
+/// Checks whether every component of `relative` (resolved from `dir`) matches
+/// a directory entry with the exact case.
+///
+/// Returns `Some(false)` if some component only matches when the case is ignored,
+/// `None` if some component doesn't exist at all.
+pub fn case_matches(dir: &Path, relative: &str) -> Option<bool> {
+    let mut dir = dir.to_path_buf();
+    let mut matches = true;
+    for segment in relative.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => {
+                dir.pop();
+                continue;
+            }
+            _ => {}
+        }
+        let names: Vec<String> = fs::read_dir(&dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        if names.iter().any(|name| name == segment) {
+            dir.push(segment);
+            continue;
+        }
+        let lower = segment.to_lowercase();
+        let name = names.iter().find(|name| name.to_lowercase() == lower)?;
+        dir.push(name);
+        matches = false;
+    }
+    Some(matches)
+}
+
+/// Checks the case of the (percent-encoded) path part of a link (see `case_matches`).
+/// `base_path` is the file of the link (relative to `root`).
+pub fn target_case_matches(path_part: &str, base_path: &Path, root: &Path) -> Option<bool> {
+    let path_part = decode(path_part);
+    let path_part = path_part.as_ref();
+    if path_part.is_empty() {
+        return Some(true);
+    }
+    match path_part.strip_prefix('/') {
+        Some(relative_path) => case_matches(root, relative_path),
+        None => case_matches(&root.join(base_path.parent()?), path_part),
+    }
+}
+
+/// Normalizes `.` and `..` of a path lexically (without touching the disk).
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolves the (decoded) path part of a link to an absolute path.
+///
+/// Root-relative paths (e.g. "/a.md") start at `root`, other paths start at
+/// the directory of `base_path` (relative to `root`). Both are resolved the same way:
+/// - With `SymlinkPolicy::Always`, the path is only normalized lexically (symlinks are kept)
+/// - Otherwise, it's canonicalized (symlinks are followed) if it exists
+///
+/// Paths that don't exist are normalized lexically, so e.g. "/../a.md" is outside `root`.
+pub fn resolve_path(
+    path_part: &str,
+    base_path: &Path,
+    root: &Path,
+    symlinks: SymlinkPolicy,
+) -> Option<PathBuf> {
+    let joined_path = match path_part.strip_prefix('/') {
+        Some(relative_path) => root.join(relative_path.trim_start_matches('/')),
+        None => root.join(base_path.parent()?).join(path_part),
+    };
+    let normalized_path = normalize_path(&joined_path);
+    match symlinks {
+        SymlinkPolicy::Always => Some(normalized_path),
+        SymlinkPolicy::InsideRoot | SymlinkPolicy::Never => {
+            Some(joined_path.canonicalize().unwrap_or(normalized_path))
+        }
+    }
+}
+
+/// Whether any component of `path` below `root` is a symlink.
+pub fn has_symlink(path: &Path, root: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut current = root.to_path_buf();
+    relative_path.components().any(|component| {
+        current.push(component);
+        current
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.file_type().is_symlink())
+    })
+}
+
+/// Percent-decodes a path (or a fragment) of a link (e.g. `my%20chapter.md` -> `my chapter.md`).
+/// It's kept as it is if the decoded bytes aren't valid UTF-8.
+pub fn decode(part: &str) -> Cow<'_, str> {
+    percent_decode_str(part)
+        .decode_utf8()
+        .unwrap_or(Cow::Borrowed(part))
+}
+
+/// Percent-encodes the characters of a file name that can't appear in a link path as they are.
+/// Other characters (including non-ASCII ones, e.g. Chinese) are kept.
+pub fn encode_name(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            ' ' => encoded.push_str("%20"),
+            '#' => encoded.push_str("%23"),
+            '%' => encoded.push_str("%25"),
+            '?' => encoded.push_str("%3F"),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// Splits a link target into its path and fragment (without `#`) parts.
+/// The query part is dropped. An empty fragment is treated as no fragment.
+pub fn split_target(target: &str) -> (&str, Option<&str>) {
+    let (rest, fragment) = match target.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment).filter(|f| !f.is_empty())),
+        None => (target, None),
+    };
+    let path_part = rest.split('?').next().unwrap_or_default();
+    (path_part, fragment)
+}
+
 /// Checks if a path is a valid relative path within the root directory.
 /// Supports:
 /// - Root-relative paths (e.g., "/a/b/c.md" where "/" maps to `root`)
@@ -35,39 +245,107 @@ pub fn check_url(path: &str) -> bool {
 /// - Current directory relative paths (e.g., "./file.md")
 /// - Parent directory relative paths (e.g., "../sibling/file.md")
 ///
-/// There are still some problems related to the title.
-/// For links that contain a title, the title portion is ignored now.
-/// We may improve later.
+/// The path and the title are percent-decoded first (e.g. `./my%20chapter.md`).
 ///
 /// Title Supports:
-/// - Pure title (e.g. "#title")
-/// - Combined-type title (e.g. "./a.md#title")
+/// - Pure title (e.g. "#title"), checked against the headings of `base_path`
+/// - Combined-type title (e.g. "./a.md#title"), checked against the headings of `a.md`
+///
+/// The headings are looked up in `index`.
+///
+/// Symlinks are followed according to `symlinks` (see `resolve_path`).
+///
+/// Directories (e.g. "./guide/") are resolved the way mdBook serves them:
+/// to their `README.md` or `index.md` chapter (see `directory_index`).
+///
+/// Titles of files that aren't markdown files are not checked.
 ///
 /// **The function behavior is still unstable.**
-pub fn is_valid_link_target(target: &str, base_path: &Path, root: &Path) -> Option<bool> {
-    if target.starts_with('#') {
-        return Some(true); // Fragments are always considered valid
-    }
+pub fn is_valid_link_target(
+    target: &str,
+    base_path: &Path,
+    root: &Path,
+    index: &AnchorIndex,
+    symlinks: SymlinkPolicy,
+) -> Option<TargetState> {
+    let (path_part, fragment) = split_target(target);
+    let path_part = decode(path_part);
+    let fragment = fragment.map(decode);
+    let path_part = path_part.as_ref();
+    let fragment = fragment.as_deref();
 
-    // Split off fragment and query parts
-    let path_part = match target.split(['#', '?']).next() {
-        Some("") => return Some(true), // Case where only fragment exists (e.g., "#title")
-        Some(part) => part,
-        None => return None,
-    };
+    // Case where only fragment exists (e.g., "#title")
+    if path_part.is_empty() {
+        return match fragment {
+            Some(fragment) => Some(anchor_state(index.has_anchor(root, base_path, fragment)?)),
+            None => Some(TargetState::Valid),
+        };
+    }
 
     // Handle the path portion
-    let full_path = if let Some(relative_path) = path_part.strip_prefix('/') {
-        root.join(relative_path)
-    } else {
-        let base_dir = root.join(base_path.parent()?);
-        let joined_path = base_dir.join(path_part);
-        match joined_path.canonicalize() {
-            Ok(canonicalized_path) => canonicalized_path,
-            Err(_) => return Some(false),
+    if symlinks == SymlinkPolicy::Never
+        && has_symlink(
+            &resolve_path(path_part, base_path, root, SymlinkPolicy::Always)?,
+            root,
+        )
+    {
+        return Some(TargetState::Symlink);
+    }
+    let full_path = resolve_path(path_part, base_path, root, symlinks)?;
+
+    // Check if the path is within the root directory and exists
+    if !full_path.starts_with(root) {
+        return Some(TargetState::OutsideRoot);
+    }
+    if !full_path.exists() {
+        return Some(TargetState::Missing);
+    }
+
+    // A directory is served as its index chapter (e.g. `guide/` -> `guide/index.html`)
+    let full_path = if full_path.is_dir() {
+        match directory_index(&full_path, root, index) {
+            Some(index_path) => index_path,
+            None => return Some(TargetState::MissingIndex),
         }
+    } else {
+        full_path
     };
 
-    // Check if the path exists and is within the root directory
-    Some(full_path.exists() && full_path.starts_with(root))
+    match fragment {
+        Some(fragment) if full_path.extension().is_some_and(|ext| ext == "md") => {
+            let relative_path = full_path.strip_prefix(root).ok()?;
+            Some(anchor_state(index.has_anchor(
+                root,
+                relative_path,
+                fragment,
+            )?))
+        }
+        _ => Some(TargetState::Valid),
+    }
+}
+
+/// The names of the files that mdBook renders as the `index.html` of a directory.
+pub const INDEX_NAMES: [&str; 2] = ["README.md", "index.md"];
+
+/// Finds the index chapter of the directory `dir` (absolute, inside `root`).
+///
+/// mdBook renders `README.md` (or `index.md`) chapters as `index.html`,
+/// so a link to the directory only works if one of them is a chapter in `index`.
+/// If `index` is empty (no book is loaded), an existing file is enough.
+pub fn directory_index(dir: &Path, root: &Path, index: &AnchorIndex) -> Option<PathBuf> {
+    INDEX_NAMES.iter().map(|name| dir.join(name)).find(|path| {
+        if index.is_empty() {
+            return path.is_file();
+        }
+        path.strip_prefix(root)
+            .is_ok_and(|relative_path| index.get(relative_path).is_some())
+    })
+}
+
+fn anchor_state(has_anchor: bool) -> TargetState {
+    if has_anchor {
+        TargetState::Valid
+    } else {
+        TargetState::MissingAnchor
+    }
 }
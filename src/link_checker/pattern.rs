@@ -0,0 +1,71 @@
+use regex::Regex;
+
+/// A pattern that matches link URLs (or paths).
+#[derive(Clone, Debug)]
+pub enum LinkPattern {
+    /// Matches the exact URL/path.
+    Exact(String),
+    /// A glob pattern (e.g. `https://example.com/*`), used when the entry has `*`, `?` or `[`.
+    /// `*` matches any characters, including `/`.
+    Glob(glob::Pattern),
+    /// A regular expression, used when the entry starts with `regex:`.
+    /// It matches if any part of the URL matches (use `^`/`$` to anchor it).
+    Regex(Regex),
+}
+
+impl LinkPattern {
+    /// Parses a pattern entry.
+    /// Returns `None` (and prompts the error) if the pattern is invalid.
+    pub fn parse(entry: &str) -> Option<Self> {
+        if let Some(regex) = entry.strip_prefix("regex:") {
+            return match Regex::new(regex) {
+                Ok(regex) => Some(LinkPattern::Regex(regex)),
+                Err(e) => {
+                    log::error!("Invalid regex pattern `{}`: {}", regex, e);
+                    None
+                }
+            };
+        }
+        if entry.contains(['*', '?', '[']) {
+            return match glob::Pattern::new(entry) {
+                Ok(pattern) => Some(LinkPattern::Glob(pattern)),
+                Err(e) => {
+                    log::error!("Invalid glob pattern `{}`: {}", entry, e);
+                    None
+                }
+            };
+        }
+        Some(LinkPattern::Exact(entry.to_string()))
+    }
+
+    pub fn is_match(&self, url: &str) -> bool {
+        match self {
+            LinkPattern::Exact(exact) => exact == url,
+            LinkPattern::Glob(pattern) => pattern.matches(url),
+            LinkPattern::Regex(regex) => regex.is_match(url),
+        }
+    }
+}
+
+/// A list of link patterns, matches if any pattern matches.
+#[derive(Clone, Debug, Default)]
+pub struct PatternList {
+    patterns: Vec<LinkPattern>,
+}
+
+impl PatternList {
+    /// Parses the pattern entries. Invalid patterns are skipped.
+    pub fn parse<'a>(entries: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            patterns: entries.into_iter().filter_map(LinkPattern::parse).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn is_match(&self, url: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(url))
+    }
+}
@@ -0,0 +1,222 @@
+use super::issue::{IssueKind, LinkIssue};
+use log::Level;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The format of the link report file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A plain JSON array of issues.
+    Json,
+    /// SARIF 2.1.0, understood by code scanning tools.
+    Sarif,
+    /// JUnit XML, understood by most CI test reporters.
+    Junit,
+}
+
+impl ReportFormat {
+    /// Parse the format name.
+    ///
+    /// Supports:
+    /// - `"json"`: `ReportFormat::Json`
+    /// - `"sarif"`: `ReportFormat::Sarif`
+    /// - `"junit"`: `ReportFormat::Junit`
+    /// - Others: Warn and use default format.
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "json" => ReportFormat::Json,
+            "sarif" => ReportFormat::Sarif,
+            "junit" => ReportFormat::Junit,
+            _ => {
+                log::error!("Invalid report format! Will use default: \"json\".");
+                ReportFormat::Json
+            }
+        }
+    }
+}
+
+/// Renders the issues in `format`.
+///
+/// `src` is the `src/` directory relative to the book root,
+/// it's prefixed to the file paths so that they are relative to the book root.
+pub fn render_report(issues: &[LinkIssue], format: ReportFormat, src: &Path) -> String {
+    match format {
+        ReportFormat::Json => render_json(issues, src),
+        ReportFormat::Sarif => render_sarif(issues, src),
+        ReportFormat::Junit => render_junit(issues, src),
+    }
+}
+
+/// Writes the report of the issues if `report_path` is configured.
+///
+/// `book_root` is the book root (where `book.toml` is), and `src` is relative to it.
+/// Failures are only logged, because the report is an extra output.
+pub fn write_configured_report(
+    issues: &[LinkIssue],
+    conf: &super::config::LinkCheckerConfig,
+    book_root: &Path,
+    src: &Path,
+) {
+    let Some(report_path) = &conf.report_path else {
+        return;
+    };
+    let path = book_root.join(report_path);
+    match write_report(issues, conf.report_format, src, &path) {
+        Ok(()) => log::info!("The link report was written to {}.", path.display()),
+        Err(e) => log::error!("Failed to write the link report: {}", e),
+    }
+}
+
+/// Renders the issues in `format` and writes them to `path`.
+pub fn write_report(
+    issues: &[LinkIssue],
+    format: ReportFormat,
+    src: &Path,
+    path: &Path,
+) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, render_report(issues, format, src))
+}
+
+/// Gets the path of the issue file relative to the book root, always with `/`.
+fn file_uri(issue: &LinkIssue, src: &Path) -> String {
+    src.join(&issue.file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warn => "warning",
+        _ => "note",
+    }
+}
+
+fn render_json(issues: &[LinkIssue], src: &Path) -> String {
+    let issues: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            json!({
+                "file": file_uri(issue, src),
+                "line": issue.line,
+                "column": issue.column,
+                "range": { "start": issue.range.start, "end": issue.range.end },
+                "kind": format!("{:?}", issue.kind),
+                "rule": issue.rule(),
+                "level": level_name(issue.level),
+                "url": issue.url,
+                "text": issue.text,
+                "message": issue.message,
+                "suggestion": issue.suggestion,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&issues).unwrap_or_default()
+}
+
+fn render_sarif(issues: &[LinkIssue], src: &Path) -> String {
+    let rules: BTreeMap<&str, IssueKind> = issues
+        .iter()
+        .map(|issue| (issue.rule(), issue.kind))
+        .collect();
+    let rules: Vec<Value> = rules
+        .values()
+        .map(|kind| {
+            json!({
+                "id": kind.rule(),
+                "shortDescription": { "text": kind.description() },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            json!({
+                "ruleId": issue.rule(),
+                "level": level_name(issue.level),
+                "message": { "text": issue.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": file_uri(issue, src) },
+                        "region": {
+                            "startLine": issue.line,
+                            "startColumn": issue.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            // `LineIndex` counts columns in characters, not in UTF-16 code units (the default).
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).unwrap_or_default()
+}
+
+/// Escapes the text for XML attributes and contents.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_junit(issues: &[LinkIssue], src: &Path) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    // A passing test case is given when there is no issue, so that the suite is never empty.
+    let tests = issues.len().max(1);
+    xml.push_str(&format!(
+        "<testsuites name=\"{0}\" tests=\"{1}\" failures=\"{2}\">\n  \
+         <testsuite name=\"link-check\" tests=\"{1}\" failures=\"{2}\">\n",
+        env!("CARGO_PKG_NAME"),
+        tests,
+        issues.len()
+    ));
+    if issues.is_empty() {
+        xml.push_str("    <testcase name=\"links\" classname=\"link-check\"/>\n");
+    }
+    for issue in issues {
+        let file = file_uri(issue, src);
+        xml.push_str(&format!(
+            "    <testcase name=\"{0}:{1}:{2} {3}\" classname=\"{0}\">\n      \
+             <failure type=\"{3}\" message=\"{4}\">{4}</failure>\n    </testcase>\n",
+            escape_xml(&file),
+            issue.line,
+            issue.column,
+            issue.rule(),
+            escape_xml(&issue.message)
+        ));
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
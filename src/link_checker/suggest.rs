@@ -0,0 +1,336 @@
+use super::anchors::AnchorIndex;
+use super::path_checker::{case_matches, decode, encode_name, split_target, target_case_matches};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A suggested replacement of a link.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The new link.
+    pub link: String,
+    /// Whether `--fix` may apply it.
+    ///
+    /// Only unambiguous fixes are: wrong case in paths, `.md` files for `.html` links,
+    /// anchors of renamed headings (the same words, e.g. `#Getting%20Started` -> `#getting-started`)
+    /// and paths written from the book root without the leading `/`.
+    /// Other suggestions (e.g. typos) are only hints.
+    pub fixable: bool,
+}
+
+impl Suggestion {
+    /// A suggestion that `--fix` may apply.
+    pub fn fix(link: String) -> Self {
+        Self {
+            link,
+            fixable: true,
+        }
+    }
+
+    /// A suggestion that is only shown.
+    pub fn hint(link: String) -> Self {
+        Self {
+            link,
+            fixable: false,
+        }
+    }
+}
+
+/// Whether `a` and `b` have the same words, ignoring case and punctuation
+/// (e.g. `Getting Started`, `getting-started` and `gettingstarted`).
+fn same_words(a: &str, b: &str) -> bool {
+    let words = |text: &str| -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    words(a) == words(b)
+}
+
+/// The edit distance (Levenshtein, in characters) between `a` and `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(current).min(row[j])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Finds the only candidate closest to `name`.
+///
+/// A candidate matches if it's equal ignoring case, or its edit distance is small enough
+/// (at most a third of the length of `name`, at least 1).
+/// Returns `None` if no candidate matches, or the best matches are ambiguous.
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let lower = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+    let mut ambiguous = false;
+    for candidate in candidates {
+        let distance = if candidate.to_lowercase() == lower {
+            0
+        } else {
+            edit_distance(name, candidate)
+        };
+        if distance > max_distance {
+            continue;
+        }
+        match best {
+            Some((best_distance, _)) if distance > best_distance => {}
+            Some((best_distance, _)) if distance == best_distance => ambiguous = true,
+            _ => {
+                best = Some((distance, candidate));
+                ambiguous = false;
+            }
+        }
+    }
+    best.filter(|_| !ambiguous).map(|(_, candidate)| candidate)
+}
+
+fn entry_names(dir: &Path, want_dir: bool) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| want_dir || entry.path().is_file())
+        .filter(|entry| !want_dir || entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Suggests a replacement of a path segment that doesn't exist in `dir`.
+///
+/// Tries (in order):
+/// - A `.md` file for a `.html` link (e.g. `a.html` -> `a.md`)
+/// - The `README.md` of a directory for a missing file (e.g. `guide.md` -> `guide/README.md`)
+/// - The closest entry (wrong case or a typo)
+///
+/// Returns the replacement and whether it's fixable (see `Suggestion::fixable`).
+fn suggest_segment(dir: &Path, segment: &str, is_last: bool) -> Option<(String, bool)> {
+    if is_last {
+        if let Some(stem) = segment.strip_suffix(".html") {
+            let md = format!("{}.md", stem);
+            if dir.join(&md).is_file() {
+                return Some((md, true));
+            }
+        }
+        let stem = segment.strip_suffix(".md").unwrap_or(segment);
+        if dir.join(stem).join("README.md").is_file() {
+            return Some((format!("{}/README.md", stem), false));
+        }
+    }
+    let names = entry_names(dir, !is_last);
+    let name = closest(segment, names.iter().map(String::as_str))?;
+    Some((
+        name.to_string(),
+        name.to_lowercase() == segment.to_lowercase(),
+    ))
+}
+
+/// Suggests an existing path for the path part of a link, in the same style as the link
+/// (e.g. `./guide/Intro.md` -> `./guide/intro.md`).
+fn suggest_path(path_part: &str, base_dir: &Path, root: &Path) -> Option<Suggestion> {
+    let (mut dir, rest) = match path_part.strip_prefix('/') {
+        Some(rest) => (root.to_path_buf(), rest),
+        None => (base_dir.to_path_buf(), path_part),
+    };
+    let segments: Vec<&str> = rest.split('/').collect();
+    let mut fixed = Vec::with_capacity(segments.len());
+    let mut changed = false;
+    let mut fixable = true;
+    for (i, segment) in segments.iter().enumerate() {
+        let is_last = i + 1 == segments.len();
+        match *segment {
+            "" | "." => {}
+            ".." => {
+                dir.pop();
+            }
+            _ if case_matches(&dir, &decode(segment)) == Some(true) => {
+                dir.push(decode(segment).as_ref())
+            }
+            _ => {
+                let (replacement, is_fixable) = suggest_segment(&dir, &decode(segment), is_last)?;
+                fixable &= is_fixable;
+                dir.push(&replacement);
+                fixed.push(
+                    replacement
+                        .split('/')
+                        .map(encode_name)
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                );
+                changed = true;
+                continue;
+            }
+        }
+        fixed.push(segment.to_string());
+    }
+    if !changed || !dir.exists() || !dir.starts_with(root) {
+        return None;
+    }
+    let prefix = if path_part.starts_with('/') { "/" } else { "" };
+    Some(Suggestion {
+        link: format!("{}{}", prefix, fixed.join("/")),
+        fixable,
+    })
+}
+
+/// Suggests the link from the chapter for a path written from the book root
+/// without the leading `/` (e.g. `guide/intro.md` in `guide/a.md` -> `intro.md`).
+///
+/// Only plain paths are tried: paths starting with `/`, `./` or `../` are meant as they are written.
+fn suggest_from_root(
+    url: &str,
+    path_part: &str,
+    base_path: &Path,
+    root: &Path,
+) -> Option<Suggestion> {
+    if path_part.is_empty() || path_part.starts_with(['/', '.']) {
+        return None;
+    }
+    let decoded = decode(path_part);
+    if case_matches(root, &decoded) != Some(true) || !root.join(decoded.as_ref()).is_file() {
+        return None;
+    }
+    Some(Suggestion::fix(relative_link(url, base_path, root)))
+}
+
+/// Resolves the file that a link points to (relative to `root`), like `is_valid_link_target`.
+fn target_file(path_part: &str, base_path: &Path, root: &Path) -> Option<PathBuf> {
+    let path_part = decode(path_part);
+    let path_part = path_part.as_ref();
+    if path_part.is_empty() {
+        return Some(base_path.to_path_buf());
+    }
+    let full_path = match path_part.strip_prefix('/') {
+        Some(relative_path) => Some(root.join(relative_path)).filter(|path| path.exists())?,
+        None => root
+            .join(base_path.parent()?)
+            .join(path_part)
+            .canonicalize()
+            .ok()?,
+    };
+    full_path.strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+/// Suggests an existing anchor of the target for a missing `#fragment`.
+fn suggest_fragment(
+    path_part: &str,
+    fragment: &str,
+    base_path: &Path,
+    root: &Path,
+    index: &AnchorIndex,
+) -> Option<Suggestion> {
+    let path = target_file(path_part, base_path, root)?;
+    let anchors = index.anchors(root, &path)?;
+    let fragment = decode(fragment);
+    let fragment = fragment.as_ref();
+    let normalized = mdbook::utils::normalize_id(fragment);
+    if anchors.contains(&normalized) {
+        return Some(Suggestion::fix(normalized));
+    }
+    let mut candidates: Vec<&str> = anchors.iter().map(String::as_str).collect();
+    candidates.sort();
+    let anchor = closest(fragment, candidates)?;
+    Some(Suggestion {
+        link: anchor.to_string(),
+        fixable: same_words(fragment, anchor),
+    })
+}
+
+/// Suggests a replacement for the broken link `url` in the file at `base_path`
+/// (relative to `root`).
+///
+/// A path written from the book root is rewritten from the chapter. Otherwise
+/// the missing path (or the missing anchor, if the path exists) is replaced
+/// by its closest match, the other parts of the link are kept as they are.
+/// Returns `None` if there is no unambiguous suggestion.
+pub fn suggest_link(
+    url: &str,
+    base_path: &Path,
+    root: &Path,
+    index: &AnchorIndex,
+) -> Option<Suggestion> {
+    let (path_part, fragment) = split_target(url);
+    let suffix = &url[path_part.len()..];
+
+    if target_case_matches(path_part, base_path, root) != Some(true)
+        || target_file(path_part, base_path, root).is_none()
+    {
+        if let Some(link) = suggest_from_root(url, path_part, base_path, root) {
+            return Some(link);
+        }
+        let base_dir = root.join(base_path.parent()?);
+        let mut path = suggest_path(path_part, &base_dir, root)?;
+        path.link.push_str(suffix);
+        return Some(path);
+    }
+
+    let fragment = fragment?;
+    let mut anchor = suggest_fragment(path_part, fragment, base_path, root, index)?;
+    let prefix = &url[..url.len() - fragment.len()];
+    anchor.link.insert_str(0, prefix);
+    Some(anchor)
+}
+
+/// Turns `target` (relative to `root`, e.g. `guide/intro.html#install`) into a link
+/// relative to the file at `base_path`, e.g. `intro.md#install` in `guide/a.md`.
+///
+/// Rendered pages are replaced by their sources: `a.html` by `a.md` (if it exists),
+/// and `index.html` by its directory (e.g. `guide/`).
+pub fn relative_link(target: &str, base_path: &Path, root: &Path) -> String {
+    let (path_part, _) = split_target(target);
+    let suffix = &target[path_part.len()..];
+    let (dir, name) = path_part.rsplit_once('/').unwrap_or(("", path_part));
+    let source = |stem: &str| {
+        root.join(decode(dir).as_ref())
+            .join(format!("{}.md", decode(stem)))
+    };
+    let name = match name.strip_suffix(".html") {
+        Some("index") => String::new(),
+        Some(stem) if source(stem).is_file() => format!("{}.md", stem),
+        _ => name.to_string(),
+    };
+
+    let base_dir: Vec<String> = base_path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let target_dir: Vec<&str> = dir
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let common = base_dir
+        .iter()
+        .zip(&target_dir)
+        .take_while(|(base, target)| **base == decode(target))
+        .count();
+
+    let mut link = "../".repeat(base_dir.len() - common);
+    for segment in &target_dir[common..] {
+        link.push_str(segment);
+        link.push('/');
+    }
+    link.push_str(&name);
+    if link.is_empty() {
+        link.push_str("./");
+    }
+    link.push_str(suffix);
+    link
+}
@@ -0,0 +1,101 @@
+use super::issue::{IssueKind, LinkIssue};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+static DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<!--\s*betterlink-(disable-next-line|disable-line|disable-file|disable|enable)((?:\s+[\w-]+)*)\s*-->")
+        .expect("the directive regex is valid")
+});
+
+/// The rules that a directive applies to. `None` means all rules.
+type Rules = Option<HashSet<IssueKind>>;
+
+fn matches(rules: &Rules, kind: IssueKind) -> bool {
+    rules.as_ref().is_none_or(|rules| rules.contains(&kind))
+}
+
+/// Parses the rule names after a directive (e.g. `missing-anchor missing-file`).
+/// Unknown rule names are prompted and ignored.
+fn parse_rules(names: &str) -> Rules {
+    let names: Vec<_> = names.split_whitespace().collect();
+    if names.is_empty() {
+        return None;
+    }
+    Some(
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let kind = IssueKind::from_rule(name);
+                if kind.is_none() {
+                    log::warn!("Unknown rule `{}` in a betterlink directive.", name);
+                }
+                kind
+            })
+            .collect(),
+    )
+}
+
+/// Suppressions from the HTML comment directives in a file:
+///
+/// - `<!-- betterlink-disable-next-line [rules] -->`: the issues on the next line
+/// - `<!-- betterlink-disable-line [rules] -->`: the issues on the same line
+/// - `<!-- betterlink-disable [rules] -->`: the issues after it, until `betterlink-enable`
+/// - `<!-- betterlink-enable [rules] -->`: ends `betterlink-disable`
+/// - `<!-- betterlink-disable-file [rules] -->`: the issues of the whole file
+///
+/// Without rule names, the directive applies to all rules.
+#[derive(Clone, Debug, Default)]
+pub struct Suppressions {
+    file: Vec<Rules>,
+    // (line, rules)
+    lines: Vec<(usize, Rules)>,
+    // (offset, disable or enable, rules), in order
+    regions: Vec<(usize, bool, Rules)>,
+}
+
+impl Suppressions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects the directives in `html`, which starts at `offset` on `line` of the file.
+    pub fn scan(&mut self, html: &str, offset: usize, line: usize) {
+        for captures in DIRECTIVE.captures_iter(html) {
+            let start = captures.get(0).map_or(0, |m| m.start());
+            let line = line + html[..start].matches('\n').count();
+            let rules = parse_rules(captures.get(2).map_or("", |m| m.as_str()));
+            match &captures[1] {
+                "disable-next-line" => self.lines.push((line + 1, rules)),
+                "disable-line" => self.lines.push((line, rules)),
+                "disable-file" => self.file.push(rules),
+                "disable" => self.regions.push((offset + start, true, rules)),
+                _ => self.regions.push((offset + start, false, rules)),
+            }
+        }
+    }
+
+    /// Whether an issue of `kind` at `line` (starting at `offset`) is suppressed.
+    pub fn is_suppressed(&self, kind: IssueKind, line: usize, offset: usize) -> bool {
+        if self.file.iter().any(|rules| matches(rules, kind)) {
+            return true;
+        }
+        if self
+            .lines
+            .iter()
+            .any(|(l, rules)| *l == line && matches(rules, kind))
+        {
+            return true;
+        }
+        self.regions
+            .iter()
+            .take_while(|(start, _, _)| *start < offset)
+            .filter(|(_, _, rules)| matches(rules, kind))
+            .last()
+            .is_some_and(|(_, disable, _)| *disable)
+    }
+
+    pub fn suppresses(&self, issue: &LinkIssue) -> bool {
+        self.is_suppressed(issue.kind, issue.line, issue.range.start)
+    }
+}
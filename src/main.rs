@@ -7,6 +7,7 @@ mod handler;
 /// About controls on type checking.
 mod link_checker;
 
+use std::path::PathBuf;
 use std::process;
 
 fn init_logger() {
@@ -39,8 +40,17 @@ fn main() {
         // Because it is compatible with most backends,
         // Most of the time, exit with 0.
         process::exit(0);
-    } else if args.subcommand_matches("check").is_some() {
-        todo!();
+    } else if let Some(check_args) = args.subcommand_matches("check") {
+        let book_dir = check_args
+            .get_one::<PathBuf>("dir")
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from("."));
+        let fix_mode = match (check_args.get_flag("fix"), check_args.get_flag("dry-run")) {
+            (_, true) => handler::FixMode::DryRun,
+            (true, false) => handler::FixMode::Write,
+            (false, false) => handler::FixMode::Off,
+        };
+        handler::check(&book_dir, fix_mode);
     } else {
         handler::handle();
     }
@@ -0,0 +1,387 @@
+use mdbook_betterlink::handler::book_checker::{check_book, load_book};
+use mdbook_betterlink::handler::config::ProcessorConfig;
+use std::fs;
+
+#[test]
+fn test_check_book() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(src.join("guide")).unwrap();
+    fs::write(dir.path().join("book.toml"), "[book]\ntitle = \"Test\"\n").unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n- [Guide](./guide/README.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n[Guide](./guide/README.md)\n\n[Missing](./missing.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("guide/README.md"),
+        "# Guide\n\n[Back](../intro.md)\n\n<https://example.com>\n",
+    )
+    .unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    assert_eq!(check_book(&md, &ProcessorConfig::default()).len(), 1);
+}
+
+#[test]
+fn test_fix_book() {
+    use mdbook_betterlink::handler::book_checker::fix_book;
+    use mdbook_betterlink::link_checker::fixer::unified_diff;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n- [Install](./install.md)\n",
+    )
+    .unwrap();
+    let intro = "# Intro\n\n[Install](./Install.md) and [page](install.html#Getting%20Started).\n\n\
+                 [Ref][r] [Missing](./missing.md) [Typo](./instal.md#getting-startd)\n\n[r]: ./INSTALL.md\n";
+    fs::write(src.join("intro.md"), intro).unwrap();
+    fs::write(src.join("install.md"), "# Getting Started\n").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let config = ProcessorConfig::default();
+    let issues = check_book(&md, &config);
+    assert_eq!(issues.len(), 5);
+
+    // Dry run doesn't touch the files.
+    let src = md.root.join("src");
+    assert_eq!(fix_book(&issues, &src, true), 2);
+    assert_eq!(fs::read_to_string(src.join("intro.md")).unwrap(), intro);
+
+    // The typo is only suggested, not fixed.
+    assert_eq!(fix_book(&issues, &src, false), 2);
+    let fixed = fs::read_to_string(src.join("intro.md")).unwrap();
+    assert_eq!(
+        fixed,
+        "# Intro\n\n[Install](./install.md) and [page](install.md#Getting%20Started).\n\n\
+         [Ref][r] [Missing](./missing.md) [Typo](./instal.md#getting-startd)\n\n[r]: ./INSTALL.md\n"
+    );
+    assert_eq!(
+        unified_diff("intro.md", intro, &fixed),
+        "--- a/intro.md\n+++ b/intro.md\n@@ -1,6 +1,6 @@\n # Intro\n \n\
+         -[Install](./Install.md) and [page](install.html#Getting%20Started).\n\
+         +[Install](./install.md) and [page](install.md#Getting%20Started).\n \n \
+         [Ref][r] [Missing](./missing.md) [Typo](./instal.md#getting-startd)\n \n"
+    );
+
+    // The anchor is fixed in the next round.
+    let md = load_book(dir.path()).unwrap();
+    let issues = check_book(&md, &config);
+    assert_eq!(fix_book(&issues, &src, false), 1);
+    assert!(
+        fs::read_to_string(src.join("intro.md"))
+            .unwrap()
+            .contains("(install.md#getting-started)")
+    );
+}
+
+#[test]
+fn test_fix_destination() {
+    use mdbook_betterlink::handler::book_checker::fix_book;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n- [Install](./install.md)\n",
+    )
+    .unwrap();
+    // The URL is repeated in the title and the text, only the destination is fixed.
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n[see [./Install.md]](./Install.md \"see ./Install.md\")\n\n\
+         ![./Install.md](\n  <./Install.md> './Install.md')\n",
+    )
+    .unwrap();
+    fs::write(src.join("install.md"), "# Install\n").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let issues = check_book(&md, &ProcessorConfig::default());
+    let src = md.root.join("src");
+    assert_eq!(fix_book(&issues, &src, false), 2);
+    assert_eq!(
+        fs::read_to_string(src.join("intro.md")).unwrap(),
+        "# Intro\n\n[see [./Install.md]](./install.md \"see ./Install.md\")\n\n\
+         ![./Install.md](\n  <./install.md> './Install.md')\n"
+    );
+}
+
+#[test]
+fn test_orphans() {
+    use mdbook_betterlink::link_checker::issue::IssueKind;
+    use mdbook_betterlink::link_checker::pattern::PatternList;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(src.join("images")).unwrap();
+    fs::create_dir_all(src.join("drafts")).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n![Logo](images/logo.png) [Linked](./linked.md)\n\n<img src=\"/images/icon.svg\">\n",
+    )
+    .unwrap();
+    fs::write(src.join("linked.md"), "# Linked\n").unwrap();
+    fs::write(src.join("orphan.md"), "# Orphan\n").unwrap();
+    fs::write(src.join("drafts/draft.md"), "# Draft\n").unwrap();
+    fs::write(src.join("images/logo.png"), "").unwrap();
+    fs::write(src.join("images/icon.svg"), "<svg/>").unwrap();
+    fs::write(src.join("images/unused.jpg"), "").unwrap();
+    fs::write(src.join("notes.txt"), "").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let mut config = ProcessorConfig::default();
+    config.link_checker_config.orphan_ignore = PatternList::parse(["drafts/*"]);
+    let issues: Vec<_> = check_book(&md, &config)
+        .into_iter()
+        .map(|issue| (issue.kind, issue.text))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (IssueKind::UnusedAsset, "images/unused.jpg".to_string()),
+            (IssueKind::OrphanPage, "orphan.md".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_included_files() {
+    use mdbook_betterlink::link_checker::issue::IssueKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(src.join("parts")).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n{{#include snippet.md}}\n\n\\{{#include escaped.md}}\n",
+    )
+    .unwrap();
+    // Nested includes are relative to the including file.
+    fs::write(
+        src.join("snippet.md"),
+        "Snippet\n\n{{ #include parts/nested.md:anchor }}\n",
+    )
+    .unwrap();
+    fs::write(src.join("parts/nested.md"), "Nested\n").unwrap();
+    fs::write(src.join("escaped.md"), "Escaped\n").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let issues: Vec<_> = check_book(&md, &ProcessorConfig::default())
+        .into_iter()
+        .map(|issue| (issue.kind, issue.text))
+        .collect();
+    assert_eq!(issues, [(IssueKind::OrphanPage, "escaped.md".to_string())]);
+}
+
+#[test]
+fn test_directory_links() {
+    use mdbook_betterlink::link_checker::issue::IssueKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    for sub in ["guide", "api", "notes", "empty"] {
+        fs::create_dir_all(src.join(sub)).unwrap();
+    }
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n- [Guide](./guide/README.md)\n- [Api](./api/index.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n[A](./guide/) [B](guide#guide) [C](./api/) [D](./guide/#nowhere)\n\n\
+         [E](./notes/) [F](./empty)\n",
+    )
+    .unwrap();
+    fs::write(src.join("guide/README.md"), "# Guide\n").unwrap();
+    fs::write(src.join("api/index.md"), "# Api\n").unwrap();
+    // Not a chapter, so mdBook won't render it.
+    fs::write(src.join("notes/README.md"), "# Notes\n").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let mut config = ProcessorConfig::default();
+    config
+        .link_checker_config
+        .rules
+        .insert(IssueKind::OrphanPage, None);
+    let issues: Vec<_> = check_book(&md, &config)
+        .into_iter()
+        .map(|issue| (issue.kind, issue.url))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (IssueKind::MissingAnchor, "./guide/#nowhere".to_string()),
+            (IssueKind::MissingIndex, "./notes/".to_string()),
+            (IssueKind::MissingIndex, "./empty".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_outside_root() {
+    use mdbook_betterlink::link_checker::issue::IssueKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(src.join("guide")).unwrap();
+    fs::create_dir_all(dir.path().join("theme")).unwrap();
+    fs::write(
+        dir.path().join("book.toml"),
+        "[book]\ntitle = \"Test\"\n\n[build]\nbuild-dir = \"out\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("theme/custom.css"), "").unwrap();
+    fs::write(dir.path().join("notes.md"), "# Notes\n").unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Guide](./guide/intro.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("guide/intro.md"),
+        "# Intro\n\n[A](/guide/intro.md) [B](/../notes.md) [C](../../notes.md)\n\n\
+         [D](../../out/index.html) [E](/../theme/custom.css) [F](../../../x.md)\n",
+    )
+    .unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    let issues: Vec<_> = check_book(&md, &ProcessorConfig::default())
+        .into_iter()
+        .map(|issue| (issue.kind, issue.url))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (IssueKind::OutsideRoot, "/../notes.md".to_string()),
+            (IssueKind::OutsideRoot, "../../notes.md".to_string()),
+            (IssueKind::ForbiddenDir, "../../out/index.html".to_string()),
+            (IssueKind::ForbiddenDir, "/../theme/custom.css".to_string()),
+            (IssueKind::OutsideRoot, "../../../x.md".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_non_canonical_src() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        dir.path().join("book.toml"),
+        "[book]\ntitle = \"Test\"\nsrc = \"src/../src\"\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n- [Install](./install.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n[A](/install.md) [B](./install.md#install) [C](install.md)\n",
+    )
+    .unwrap();
+    fs::write(src.join("install.md"), "# Install\n").unwrap();
+
+    let md = load_book(dir.path()).unwrap();
+    assert!(check_book(&md, &ProcessorConfig::default()).is_empty());
+}
+
+#[test]
+fn test_check_exit_status() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("intro.md"),
+        "# Intro\n\n[Site](http://example.com)\n",
+    )
+    .unwrap();
+    let check_with = |link_checker: &str| {
+        fs::write(
+            dir.path().join("book.toml"),
+            format!(
+                "[book]\ntitle = \"Test\"\n\n[preprocessor.betterlink.link_checker]\n{}",
+                link_checker
+            ),
+        )
+        .unwrap();
+        std::process::Command::new(env!("CARGO_BIN_EXE_mdbook-betterlink"))
+            .arg("check")
+            .arg(dir.path())
+            .output()
+            .unwrap()
+            .status
+            .success()
+    };
+
+    // `insecure-http` is a warning, only errors fail by default.
+    assert!(check_with(""));
+    assert!(!check_with("fail_on = \"warning\"\n"));
+    assert!(check_with(
+        "fail_on = \"warning\"\n\n[preprocessor.betterlink.link_checker.rules]\ninsecure-http = \"info\"\n"
+    ));
+    assert!(!check_with(
+        "[preprocessor.betterlink.link_checker.rules]\ninsecure-http = \"error\"\n"
+    ));
+}
+
+#[test]
+fn test_old_tag_adder_anchors() {
+    use mdbook_betterlink::link_checker::issue::IssueKind;
+
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](./intro.md)\n",
+    )
+    .unwrap();
+    fs::write(src.join("intro.md"), "# my_func\n\n[x](#myfunc)\n").unwrap();
+    let check_with = |use_old_tag_adder: bool| {
+        fs::write(
+            dir.path().join("book.toml"),
+            format!(
+                "[book]\ntitle = \"Test\"\n\n[preprocessor.betterlink]\nuse_old_tag_adder = {}\n",
+                use_old_tag_adder
+            ),
+        )
+        .unwrap();
+        let md = load_book(dir.path()).unwrap();
+        let config = mdbook_betterlink::handler::processor::get_processor_config(&md.config);
+        check_book(&md, &config)
+            .into_iter()
+            .map(|issue| issue.kind)
+            .collect::<Vec<_>>()
+    };
+
+    // `add_a_tag` renders `id="myfunc"`, `tag_adder` renders `id="my_func"`.
+    assert_eq!(check_with(true), []);
+    assert_eq!(check_with(false), [IssueKind::MissingAnchor]);
+}
@@ -0,0 +1,87 @@
+use mdbook::BookItem;
+use mdbook::book::Book;
+use mdbook::preprocess::PreprocessorContext;
+use mdbook_betterlink::handler::book_handler::handle;
+use mdbook_betterlink::handler::processor::get_processor_config;
+use serde_json::json;
+use std::fs;
+
+/// Builds the input that mdbook gives to the preprocessor.
+fn make_input(
+    root: &std::path::Path,
+    link_checker: serde_json::Value,
+) -> (PreprocessorContext, Book) {
+    let input = json!([
+        {
+            "root": root,
+            "config": {
+                "book": { "src": "src" },
+                "preprocessor": { "betterlink": { "link_checker": link_checker } },
+            },
+            "renderer": "html",
+            "mdbook_version": "0.4.52",
+        },
+        {
+            "sections": [{
+                "Chapter": {
+                    "name": "A",
+                    "content": "# Title\n\n## Sub Title\n\n[B](./b.md)\n",
+                    "number": null,
+                    "sub_items": [],
+                    "path": "a.md",
+                    "source_path": "a.md",
+                    "parent_names": [],
+                }
+            }],
+            "__non_exhaustive": null,
+        }
+    ]);
+    serde_json::from_value(input).unwrap()
+}
+
+#[test]
+fn test_handle_reports_source_positions() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    let report_path = root.join("report.json");
+
+    let (ctx, book) = make_input(&root, json!({ "report_path": "report.json" }));
+    let config = get_processor_config(&ctx.config);
+    assert!(handle(book, config, &ctx).is_ok());
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report_path).unwrap()).unwrap();
+    assert_eq!(report[0]["file"], "src/a.md");
+    assert_eq!(report[0]["line"], 5);
+    assert_eq!(report[0]["column"], 1);
+}
+
+#[test]
+fn test_handle_fail_on() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+
+    let (ctx, book) = make_input(&root, json!({ "fail_on": "error" }));
+    let config = get_processor_config(&ctx.config);
+    let error = handle(book, config, &ctx).unwrap_err();
+    assert!(error.to_string().contains("a.md:5:1"));
+}
+
+#[test]
+fn test_handle_non_canonical_root() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/b.md"), "# B\n").unwrap();
+    let root = dir.path().join("src/..");
+
+    let (ctx, mut book) = make_input(&root, json!({ "fail_on": "warning" }));
+    if let Some(BookItem::Chapter(chapter)) = book.sections.first_mut() {
+        chapter
+            .content
+            .push_str("\n[Root](/b.md) [Dot](./b.md#b)\n");
+    }
+    let config = get_processor_config(&ctx.config);
+    assert!(handle(book, config, &ctx).is_ok());
+}
@@ -0,0 +1,108 @@
+use mdbook_betterlink::link_checker::anchors::AnchorIndex;
+use mdbook_betterlink::link_checker::check_link;
+use mdbook_betterlink::link_checker::config::{ExternalConfig, LinkCheckerConfig};
+use mdbook_betterlink::link_checker::external::check_external_links;
+use mdbook_betterlink::link_checker::issue::IssueKind;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Starts a local HTTP stand-in server, returns its address and the count of requests.
+///
+/// - `/ok`: 200
+/// - `/no-head`: 405 for `HEAD`, 200 for `GET`
+/// - Others: 404
+fn start_server() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Skip the headers.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 2 {
+                line.clear();
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default();
+            let status = match parts.next().unwrap_or_default() {
+                "/ok" => "200 OK",
+                "/no-head" if method == "HEAD" => "405 Method Not Allowed",
+                "/no-head" => "200 OK",
+                _ => "404 Not Found",
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+        }
+    });
+    (address, requests)
+}
+
+#[test]
+fn test_external_links() {
+    let (address, requests) = start_server();
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+
+    let content = format!(
+        "[Ok]({0}/ok) [Ok again]({0}/ok#part) [No head]({0}/no-head)\n\n\
+         [Missing]({0}/missing)\n\n\
+         [Unreachable](http://127.0.0.1:1/)\n",
+        address
+    );
+    let conf = LinkCheckerConfig {
+        check_external: true,
+        external: ExternalConfig {
+            timeout: 5,
+            retries: 0,
+            host_interval: 0,
+            ..ExternalConfig::default()
+        },
+        // The test server is at `http://127.0.0.1`.
+        rules: HashMap::from([(IssueKind::InsecureHttp, None), (IssueKind::IpHost, None)]),
+        ..LinkCheckerConfig::default()
+    };
+    let mut found = Vec::new();
+    let issues = check_link(
+        &content,
+        &Some(PathBuf::from("a.md")),
+        &root,
+        &AnchorIndex::new(false),
+        &conf,
+        &mut found,
+    );
+    assert!(issues.is_empty());
+
+    let issues = check_external_links(&found, &conf, &root, &root);
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.line))
+        .collect();
+    assert_eq!(
+        kinds,
+        [(IssueKind::HttpError, 3), (IssueKind::UnreachableUrl, 5)]
+    );
+    assert!(issues[0].message.contains("HTTP 404"));
+    // `/ok` once, `/no-head` twice (HEAD then GET), `/missing` twice (HEAD then GET).
+    assert_eq!(requests.load(Ordering::SeqCst), 5);
+    assert!(root.join(".betterlink-cache.json").exists());
+
+    // The responses are cached, so the server isn't requested again.
+    let issues = check_external_links(&found, &conf, &root, &root);
+    assert_eq!(issues.len(), 2);
+    assert_eq!(requests.load(Ordering::SeqCst), 5);
+}
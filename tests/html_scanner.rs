@@ -0,0 +1,35 @@
+use mdbook_betterlink::link_checker::html_scanner::{HtmlChunks, scan_attributes};
+
+#[test]
+fn test_scan_attributes() {
+    let html = r#"<!-- <a href="commented.md"> --><A HREF='./a.md' class=x>
+<img alt="" src=./logo.png/></a><iframe
+  src="https://example.com"></iframe>"#;
+    let attributes = scan_attributes(html);
+    let links: Vec<_> = attributes
+        .iter()
+        .filter(|a| a.is_link())
+        .map(|a| (a.tag.as_str(), a.name.as_str(), a.value))
+        .collect();
+    assert_eq!(
+        links,
+        [
+            ("a", "href", "./a.md"),
+            ("img", "src", "./logo.png/"),
+            ("iframe", "src", "https://example.com"),
+        ]
+    );
+    let a = attributes.iter().find(|a| a.value == "./a.md").unwrap();
+    assert_eq!(&html[a.offset..a.offset + a.value.len()], "./a.md");
+}
+
+#[test]
+fn test_html_chunks() {
+    let mut chunks = HtmlChunks::new();
+    chunks.push("<div>\n", 10);
+    chunks.push("<a href=\"x\">\n", 30);
+    assert_eq!(chunks.text(), "<div>\n<a href=\"x\">\n");
+    assert_eq!(chunks.source_offset(0), 10);
+    assert_eq!(chunks.source_offset(6), 30);
+    assert_eq!(chunks.source_offset(15), 39);
+}
@@ -0,0 +1,680 @@
+use log::Level;
+use mdbook_betterlink::link_checker::anchors::{AnchorIndex, collect_anchors};
+use mdbook_betterlink::link_checker::check_link;
+use mdbook_betterlink::link_checker::config::{FailOn, LinkCheckerConfig, SymlinkPolicy};
+use mdbook_betterlink::link_checker::contact::{validate_mailto, validate_tel};
+use mdbook_betterlink::link_checker::domain::DomainList;
+use mdbook_betterlink::link_checker::issue::{IssueKind, LinkIssue};
+use mdbook_betterlink::link_checker::line_index::LineIndex;
+use mdbook_betterlink::link_checker::path_checker::{
+    TargetState, case_matches, decode, is_valid_link_target,
+};
+use mdbook_betterlink::link_checker::pattern::PatternList;
+use mdbook_betterlink::link_checker::suggest::{edit_distance, relative_link};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Creates an empty (canonical) root directory, which lives as long as the `TempDir`.
+fn temp_root() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    (dir, root)
+}
+
+/// Checks `content` as the chapter `b.md` under `root`.
+fn check(content: &str, root: &Path, conf: &LinkCheckerConfig) -> Vec<LinkIssue> {
+    check_link(
+        content,
+        &Some(PathBuf::from("b.md")),
+        root,
+        &AnchorIndex::new(false),
+        conf,
+        &mut Vec::new(),
+    )
+}
+
+#[test]
+fn test_collect_anchors() {
+    let anchors = collect_anchors(
+        "# Hello World\n\n# Hello World\n\n# 中文标题\n\n# Custom {#my-id}\n\n```md\n# Not a heading\n```\n",
+        false,
+        false,
+    );
+    assert!(anchors.contains("helloworld")); // Generated by `tag_adder`
+    assert!(anchors.contains("helloworld-1"));
+    assert!(anchors.contains("hello-world")); // Generated by mdbook
+    assert!(anchors.contains("hello-world-1"));
+    assert!(anchors.contains("中文标题"));
+    assert!(anchors.contains("my-id"));
+    assert!(!anchors.contains("custom")); // No native ID for a heading with `{#id}`
+    assert!(!anchors.contains("not-a-heading"));
+
+    // `old_tag_adder` drops `_` and doesn't number duplicates.
+    let anchors = collect_anchors("# my_func\n\n# my_func\n", false, true);
+    assert!(anchors.contains("myfunc"));
+    assert!(!anchors.contains("myfunc-1"));
+    assert!(anchors.contains("my_func")); // Generated by mdbook
+    assert!(anchors.contains("my_func-1"));
+}
+
+#[test]
+fn test_fragment_target() {
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n\n## Sub Title\n").unwrap();
+    fs::write(root.join("b.md"), "# Local\n\n[a](./a.md#title)\n").unwrap();
+
+    let index = AnchorIndex::new(false);
+    let target_state = |target| {
+        is_valid_link_target(
+            target,
+            Path::new("b.md"),
+            &root,
+            &index,
+            SymlinkPolicy::InsideRoot,
+        )
+    };
+    assert_eq!(target_state("#local"), Some(TargetState::Valid));
+    assert_eq!(target_state("#nowhere"), Some(TargetState::MissingAnchor));
+    assert_eq!(target_state("./a.md#title"), Some(TargetState::Valid));
+    assert_eq!(target_state("./a.md#sub-title"), Some(TargetState::Valid));
+    assert_eq!(
+        target_state("./a.md#missing"),
+        Some(TargetState::MissingAnchor)
+    );
+    assert_eq!(target_state("./a.md#"), Some(TargetState::Valid));
+    assert_eq!(target_state("./c.md#title"), Some(TargetState::Missing));
+}
+
+#[test]
+fn test_anchor_index() {
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Old Title\n").unwrap();
+
+    // The index takes priority over the file on the disk.
+    let mut index = AnchorIndex::new(false);
+    index.insert(PathBuf::from("a.md"), "# Intro {#intro}\n\n# Intro\n");
+    let anchors = index.get(Path::new("a.md")).unwrap();
+    assert!(anchors.explicit.contains("intro"));
+    assert!(anchors.all.contains("intro-1")); // The explicit ID is never generated again
+    assert_eq!(
+        index.has_anchor(&root, Path::new("a.md"), "intro"),
+        Some(true)
+    );
+    assert_eq!(
+        index.has_anchor(&root, Path::new("a.md"), "oldtitle"),
+        Some(false)
+    );
+    assert_eq!(index.has_anchor(&root, Path::new("b.md"), "intro"), None);
+}
+
+#[test]
+fn test_fail_on() {
+    assert_eq!(FailOn::parse("error"), FailOn::Error);
+    assert_eq!(FailOn::parse("unknown"), FailOn::Never);
+    assert!(FailOn::Error.is_met(Level::Error));
+    assert!(!FailOn::Error.is_met(Level::Warn));
+    assert!(FailOn::Warning.is_met(Level::Warn));
+    assert!(!FailOn::Warning.is_met(Level::Info));
+    assert!(!FailOn::Never.is_met(Level::Error));
+    assert_eq!(FailOn::Never.for_check(), FailOn::Error);
+    assert_eq!(FailOn::Warning.for_check(), FailOn::Warning);
+}
+
+#[test]
+fn test_line_index() {
+    let text = "# 标题\n\n中文 [链接](./a.md)\nabc";
+    let lines = LineIndex::new(text);
+    assert_eq!(lines.position(0), (1, 1));
+    assert_eq!(lines.position(text.find("[链接]").unwrap()), (3, 4));
+    assert_eq!(lines.position(text.find("abc").unwrap()), (4, 1));
+    assert_eq!(lines.position(text.len() + 10), (4, 4));
+    assert_eq!(lines.position(3), (1, 3)); // Inside `标`
+}
+
+#[test]
+fn test_reference_links() {
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n").unwrap();
+
+    let content = "[Good][a] [Bad][B] [a][] [c]\n\n\
+                   [a]: ./a.md\n\
+                   [b]: ./missing.md\n\
+                   [c]: ./a.md#nowhere\n\
+                   [Never  Used]: ./a.md\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    let kinds: Vec<_> = issues.iter().map(|issue| issue.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            IssueKind::InvalidPath,
+            IssueKind::MissingAnchor,
+            IssueKind::UnusedReference
+        ]
+    );
+    assert_eq!(issues[2].line, 6);
+    assert_eq!(issues[2].level, log::Level::Warn);
+}
+
+#[test]
+fn test_image_links() {
+    let (_dir, root) = temp_root();
+    fs::create_dir_all(root.join("images")).unwrap();
+    fs::write(root.join("images/logo.svg"), "<svg/>").unwrap();
+    fs::write(root.join("a.md"), "# Title\n").unwrap();
+
+    let content = "![Logo](./images/logo.svg)\n\n\
+                   ![Missing](./images/missing.png)\n\n\
+                   [![](./images/logo.svg)](./a.md)\n\n\
+                   [![Logo](./images/missing.png)](./nowhere.md)\n";
+    let conf = LinkCheckerConfig {
+        warn_empty_alt: true,
+        ..LinkCheckerConfig::default()
+    };
+    let issues = check(content, &root, &conf);
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.line))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (IssueKind::MissingImage, 3),
+            (IssueKind::EmptyAlt, 5),
+            (IssueKind::MissingImage, 7),
+            (IssueKind::InvalidPath, 7),
+        ]
+    );
+}
+
+#[test]
+fn test_html_links() {
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n\n<a id=\"custom\"></a>\n").unwrap();
+
+    let content = "<div>\n  <a href=\"./a.md#custom\">A</a>\n  <img src=\"./missing.png\">\n</div>\n\n\
+                   Inline <a href=\"./nowhere.md\">link</a>.\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.line, issue.column))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (IssueKind::MissingImage, 3, 13),
+            (IssueKind::InvalidPath, 6, 17)
+        ]
+    );
+}
+
+#[test]
+fn test_link_patterns() {
+    let patterns =
+        PatternList::parse(["./exact.md", "https://*.example.com/*", "regex:^\\./v\\d+/"]);
+    assert!(patterns.is_match("./exact.md"));
+    assert!(!patterns.is_match("./exact.md#title"));
+    assert!(patterns.is_match("https://docs.example.com/a/b.html"));
+    assert!(!patterns.is_match("https://example.org/"));
+    assert!(patterns.is_match("./v2/a.md"));
+    assert!(!patterns.is_match("./vx/a.md"));
+
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n").unwrap();
+
+    let content = "[Blocked](https://staging.example.com/a) [Fine](https://example.org/)\n\n\
+                   [Api](./api/index.html#Foo) [Api blocked](./api/v1.html)\n\n\
+                   [Missing](./missing.md)\n";
+    let conf = LinkCheckerConfig {
+        black_list: PatternList::parse(["https://*.example.com/*", "regex:/v1\\."]),
+        allow_list: PatternList::parse(["./api/*"]),
+        ..LinkCheckerConfig::default()
+    };
+    let issues = check(content, &root, &conf);
+    let urls: Vec<_> = issues.iter().map(|issue| issue.url.as_str()).collect();
+    assert_eq!(urls, ["https://staging.example.com/a", "./missing.md"]);
+}
+
+#[test]
+fn test_rule_severity() {
+    let table: toml::value::Table = toml::from_str(
+        "prompt_level = 2\n\
+         [rules]\n\
+         missing-anchor = \"info\"\n\
+         missing-file = \"error\"\n\
+         unused-reference = \"off\"\n\
+         unknown-rule = \"warn\"\n\
+         broken-reference = \"loud\"\n",
+    )
+    .unwrap();
+    let conf = LinkCheckerConfig::parse(&table);
+    assert_eq!(conf.rules.len(), 3);
+    assert_eq!(conf.level_of(IssueKind::Broken), Some(Level::Warn));
+    assert_eq!(conf.level_of(IssueKind::UnusedReference), None);
+
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n").unwrap();
+
+    let content = "[Anchor](./a.md#nowhere) [Missing](./missing.md) [Bad][b]\n\n\
+                   [b]: ./a.md\n\
+                   [unused]: ./a.md\n";
+    let issues = check(content, &root, &conf);
+    let levels: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.level))
+        .collect();
+    assert_eq!(
+        levels,
+        [
+            (IssueKind::MissingAnchor, Level::Info),
+            (IssueKind::InvalidPath, Level::Error)
+        ]
+    );
+}
+
+#[test]
+fn test_suppressions() {
+    let (_dir, root) = temp_root();
+    fs::write(root.join("a.md"), "# Title\n").unwrap();
+
+    let content = "<!-- betterlink-disable-next-line -->\n\
+                   [One](./one.md)\n\n\
+                   [Two](./two.md) <!-- betterlink-disable-line missing-file -->\n\n\
+                   <!-- betterlink-disable missing-anchor -->\n\n\
+                   [Three](./a.md#nowhere) [Four](./four.md)\n\n\
+                   <!-- betterlink-enable -->\n\n\
+                   [Five](./a.md#nowhere)\n";
+    let mut found = Vec::new();
+    let issues = check_link(
+        content,
+        &Some(PathBuf::from("b.md")),
+        &root,
+        &AnchorIndex::new(false),
+        &LinkCheckerConfig::default(),
+        &mut found,
+    );
+    let urls: Vec<_> = issues.iter().map(|issue| issue.url.as_str()).collect();
+    assert_eq!(urls, ["./four.md", "./a.md#nowhere"]);
+    assert_eq!(issues[1].line, 12);
+    assert_eq!(found.len(), 5);
+    assert_eq!(found[0].suppressed.len(), IssueKind::ALL.len());
+    assert_eq!(found[1].suppressed, [IssueKind::InvalidPath]);
+
+    let content = "[One](./one.md)\n\n<!-- betterlink-disable-file -->\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn test_suggestions() {
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("标题", "标签"), 1);
+
+    let (_dir, root) = temp_root();
+    fs::create_dir_all(root.join("guide")).unwrap();
+    fs::create_dir_all(root.join("images")).unwrap();
+    fs::write(root.join("guide/README.md"), "# Guide\n").unwrap();
+    fs::write(root.join("guide/install.md"), "# Getting Started\n").unwrap();
+    fs::write(root.join("images/logo.png"), "").unwrap();
+
+    let content = "[A](./Guide/install.md) [B](./guide/instal.md#x) [C](./guide/install.html)\n\n\
+                   [D](./guide.md) [E](./guide/install.md#getting-startd) [F](./guide/install.md#Getting%20Started)\n\n\
+                   ![G](/images/logo.jpg) [H](./nothing-like-it.md)\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    let suggestions: Vec<_> = issues
+        .iter()
+        .map(|issue| issue.suggestion.as_deref())
+        .collect();
+    assert_eq!(
+        suggestions,
+        [
+            Some("./guide/install.md"),
+            Some("./guide/install.md#x"),
+            Some("./guide/install.md"),
+            Some("./guide/README.md"),
+            Some("./guide/install.md#getting-started"),
+            Some("./guide/install.md#getting-started"),
+            Some("/images/logo.png"),
+            None,
+        ]
+    );
+    assert!(
+        issues[0]
+            .message
+            .ends_with("Did you mean `./guide/install.md`?")
+    );
+}
+
+#[test]
+fn test_root_relative_paths() {
+    let (_dir, root) = temp_root();
+    fs::create_dir_all(root.join("guide")).unwrap();
+    fs::create_dir_all(root.join("images")).unwrap();
+    fs::write(root.join("guide/install.md"), "# Install\n").unwrap();
+    fs::write(root.join("images/logo.png"), "").unwrap();
+
+    // Written from the book root in `guide/a.md`.
+    let content = "[A](guide/install.md#install) ![B](images/logo.png) [C](./guide/install.md)\n";
+    let issues = check_link(
+        content,
+        &Some(PathBuf::from("guide/a.md")),
+        &root,
+        &AnchorIndex::new(false),
+        &LinkCheckerConfig::default(),
+        &mut Vec::new(),
+    );
+    let suggestions: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.suggestion.as_deref(), issue.fixable))
+        .collect();
+    assert_eq!(
+        suggestions,
+        [
+            (Some("install.md#install"), true),
+            (Some("../images/logo.png"), true),
+            (None, false),
+        ]
+    );
+}
+
+#[test]
+fn test_case_mismatch() {
+    let (_dir, root) = temp_root();
+    fs::create_dir_all(root.join("Guide")).unwrap();
+    fs::write(root.join("Guide/intro.md"), "# Intro\n").unwrap();
+
+    assert_eq!(case_matches(&root, "./Guide/intro.md"), Some(true));
+    assert_eq!(case_matches(&root, "guide/../Guide/Intro.md"), Some(false));
+    assert_eq!(case_matches(&root, "Guide/missing.md"), None);
+
+    let content = "[A](./guide/intro.md#intro) [B](/Guide/INTRO.md) [C](./Guide/intro.md)\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.suggestion.as_deref()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (IssueKind::CaseMismatch, Some("./Guide/intro.md#intro")),
+            (IssueKind::CaseMismatch, Some("/Guide/intro.md")),
+        ]
+    );
+
+    let conf = LinkCheckerConfig {
+        strict_case: false,
+        ..LinkCheckerConfig::default()
+    };
+    assert!(
+        check(content, &root, &conf)
+            .iter()
+            .all(|issue| issue.kind != IssueKind::CaseMismatch)
+    );
+}
+
+#[test]
+fn test_encoded_paths() {
+    assert_eq!(decode("my%20chapter.md"), "my chapter.md");
+    assert_eq!(decode("%E4%B8%AD%E6%96%87.md"), "中文.md");
+    assert_eq!(decode("bad%FF.md"), "bad%FF.md");
+
+    let (_dir, root) = temp_root();
+    fs::write(root.join("my chapter.md"), "# 标题\n").unwrap();
+    fs::write(root.join("中文.md"), "# Title\n").unwrap();
+
+    let content = "[A](./my%20chapter.md) [B](./%E4%B8%AD%E6%96%87.md#title) [C](./中文.md)\n\n\
+                   [D](<./my chapter.md>) [E](./my%20chaptr.md)\n\n\
+                   [F](./my%20chapter.md#%E6%A0%87%E9%A2%98)\n";
+    let issues = check(content, &root, &LinkCheckerConfig::default());
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.suggestion.as_deref()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (IssueKind::UnencodedSpace, Some("./my%20chapter.md")),
+            (IssueKind::InvalidPath, Some("./my%20chapter.md")),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_policy() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().canonicalize().unwrap();
+    let root = base.join("src");
+    fs::create_dir_all(root.join("guide")).unwrap();
+    fs::create_dir_all(base.join("external")).unwrap();
+    fs::write(root.join("guide/a.md"), "# Title\n").unwrap();
+    fs::write(base.join("external/a.md"), "# Title\n").unwrap();
+    symlink(root.join("guide"), root.join("alias")).unwrap();
+    symlink(base.join("external"), root.join("ext")).unwrap();
+
+    let content = "[A](./alias/a.md#title) [B](/alias/a.md) [C](./ext/a.md) [D](/ext/a.md)\n";
+    let check_with = |policy| {
+        let conf = LinkCheckerConfig {
+            follow_symlinks: policy,
+            ..LinkCheckerConfig::default()
+        };
+        check(content, &root, &conf)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.url))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        check_with(SymlinkPolicy::InsideRoot),
+        [
+            (IssueKind::OutsideRoot, "./ext/a.md".to_string()),
+            (IssueKind::OutsideRoot, "/ext/a.md".to_string()),
+        ]
+    );
+    assert!(check_with(SymlinkPolicy::Always).is_empty());
+    let kinds: Vec<_> = check_with(SymlinkPolicy::Never)
+        .into_iter()
+        .map(|(kind, _)| kind)
+        .collect();
+    assert_eq!(kinds, [IssueKind::Symlink; 4]);
+}
+
+#[test]
+fn test_contact_links() {
+    assert!(validate_mailto("mailto:a@example.com,b.c@example.org", false).is_ok());
+    assert!(
+        validate_mailto(
+            "mailto:a@example.com?subject=Hi%20there&cc=b@example.com",
+            false
+        )
+        .is_ok()
+    );
+    assert!(validate_mailto("mailto:?to=a@example.com&subject=Hi", false).is_ok());
+    assert!(validate_mailto("mailto:?subject=Hi", false).is_err());
+    assert!(validate_mailto("mailto:\"john doe\"@example.com", false).is_ok());
+    assert!(validate_mailto("mailto:a..b@example.com", false).is_err());
+    assert!(validate_mailto("mailto:a@example.com?cc=nobody", false).is_err());
+    assert!(validate_mailto("mailto:a@example.com?subject", false).is_err());
+    assert!(validate_tel("tel:+1-202-555-0143").is_ok());
+    assert!(validate_tel("tel:+44 20 7946 0958;ext=12").is_ok());
+    assert!(validate_tel("tel:555-0143").is_err());
+    assert!(validate_tel("tel:+0123").is_err());
+    assert!(validate_tel("tel:+1234567890123456").is_err());
+
+    let content = "<a@example.com> [B](mailto:a@@example.com) [C](tel:+1-202-555-0143)\n\n\
+                   [D](tel:12345) <mailto:b@example.com>\n";
+    let check_with = |forbid_email| {
+        let conf = LinkCheckerConfig {
+            forbid_email,
+            ..LinkCheckerConfig::default()
+        };
+        check(content, Path::new("."), &conf)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.url))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        check_with(false),
+        [
+            (IssueKind::InvalidEmail, "mailto:a@@example.com".to_string()),
+            (IssueKind::InvalidPhone, "tel:12345".to_string()),
+        ]
+    );
+    assert_eq!(
+        check_with(true),
+        [
+            (IssueKind::ForbiddenEmail, "a@example.com".to_string()),
+            (
+                IssueKind::ForbiddenEmail,
+                "mailto:a@@example.com".to_string()
+            ),
+            (IssueKind::InvalidPhone, "tel:12345".to_string()),
+            (
+                IssueKind::ForbiddenEmail,
+                "mailto:b@example.com".to_string()
+            ),
+        ]
+    );
+
+    let conf = LinkCheckerConfig {
+        black_list: PatternList::parse(["mailto:leak@corp.example.com", "tel:+15550100"]),
+        ..LinkCheckerConfig::default()
+    };
+    let content = "[A](mailto:leak@corp.example.com) [B](tel:+15550100) [C](tel:+1-202-555-0143)\n";
+    let issues: Vec<_> = check(content, Path::new("."), &conf)
+        .into_iter()
+        .map(|issue| (issue.kind, issue.url))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (
+                IssueKind::Blacklisted,
+                "mailto:leak@corp.example.com".to_string()
+            ),
+            (IssueKind::Blacklisted, "tel:+15550100".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_url_schemes() {
+    let content = "[A](https://example.com) [B](http://example.com/a?b=c) [C](file:///C:/docs/a.md)\n\n\
+                   <a href=\"javascript:alert(1)\">D</a> [E](ftp://example.com/a.zip) <HTTP://example.com>\n\n\
+                   <a href=\" http://example.com/\">F</a>\n";
+    let check_with = |schemes: &[&str]| {
+        let conf = LinkCheckerConfig {
+            schemes: schemes.iter().map(|scheme| scheme.to_string()).collect(),
+            ..LinkCheckerConfig::default()
+        };
+        check(content, Path::new("."), &conf)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.suggestion))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        check_with(&["https", "http", "mailto", "tel"]),
+        [
+            (
+                IssueKind::InsecureHttp,
+                Some("https://example.com/a?b=c".to_string())
+            ),
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+            (
+                IssueKind::InsecureHttp,
+                Some("https://example.com".to_string())
+            ),
+            (
+                IssueKind::InsecureHttp,
+                Some("https://example.com/".to_string())
+            ),
+        ]
+    );
+    assert_eq!(
+        check_with(&["https", "ftp"]),
+        [
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+            (IssueKind::ForbiddenScheme, None),
+        ]
+    );
+}
+
+#[test]
+fn test_domains() {
+    let list = DomainList::parse(["*.corp.local", "Staging.Example.com", "[::1]"]);
+    assert!(list.is_match("wiki.corp.local"));
+    assert!(list.is_match("a.b.corp.local"));
+    assert!(!list.is_match("corp.local"));
+    assert!(!list.is_match("xcorp.local"));
+    assert!(list.is_match("staging.example.com"));
+    assert!(list.is_match("::1"));
+
+    let (_dir, root) = temp_root();
+    fs::create_dir_all(root.join("guide")).unwrap();
+    fs::write(root.join("guide/intro.md"), "# Install\n").unwrap();
+    fs::write(root.join("guide/README.md"), "# Guide\n").unwrap();
+
+    let chapter = Path::new("guide/a.md");
+    assert_eq!(
+        relative_link("guide/intro.html#install", chapter, &root),
+        "intro.md#install"
+    );
+    assert_eq!(relative_link("", chapter, &root), "../");
+    assert_eq!(
+        relative_link("api/a.html?x", chapter, &root),
+        "../api/a.html?x"
+    );
+
+    let content = "[A](https://wiki.corp.local/x) [B](https://10.0.0.1/) [C](https://[::1]:8080/)\n\n\
+                   [D](https://example.com/book/guide/intro.html#install) [E](https://example.com/book)\n\n\
+                   [F](https://example.com/other/) [G](https://example.com/book/guide/index.html)\n";
+    let check_with = |allow_domains: &[&str]| {
+        let conf = LinkCheckerConfig {
+            deny_domains: DomainList::parse(["*.corp.local"]),
+            allow_domains: DomainList::parse(allow_domains.iter().copied()),
+            site_url: url::Url::parse("https://example.com/book/").ok(),
+            ..LinkCheckerConfig::default()
+        };
+        check(content, &root, &conf)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.suggestion))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        check_with(&[]),
+        [
+            (IssueKind::DeniedDomain, None),
+            (IssueKind::IpHost, None),
+            (IssueKind::IpHost, None),
+            (
+                IssueKind::SelfLink,
+                Some("guide/intro.md#install".to_string())
+            ),
+            (IssueKind::SelfLink, Some("./".to_string())),
+            (IssueKind::SelfLink, Some("guide/".to_string())),
+        ]
+    );
+    assert_eq!(
+        check_with(&["example.com", "*.corp.local", "::1"]),
+        [
+            (IssueKind::DeniedDomain, None),
+            (IssueKind::DeniedDomain, None),
+            (
+                IssueKind::SelfLink,
+                Some("guide/intro.md#install".to_string())
+            ),
+            (IssueKind::SelfLink, Some("./".to_string())),
+            (IssueKind::SelfLink, Some("guide/".to_string())),
+        ]
+    );
+}
@@ -0,0 +1,57 @@
+use log::Level;
+use mdbook_betterlink::link_checker::issue::{IssueKind, LinkIssue};
+use mdbook_betterlink::link_checker::report::{ReportFormat, render_report};
+use std::path::{Path, PathBuf};
+
+fn sample_issues() -> Vec<LinkIssue> {
+    vec![LinkIssue {
+        file: PathBuf::from("guide/a.md"),
+        line: 3,
+        column: 5,
+        range: 20..38,
+        kind: IssueKind::InvalidPath,
+        url: "./b.md".to_string(),
+        text: "<B & C>".to_string(),
+        level: Level::Error,
+        message: "[<B & C>](./b.md) isn't a valid URL (or path).".to_string(),
+        suggestion: None,
+        fixable: false,
+    }]
+}
+
+#[test]
+fn test_json_report() {
+    let report = render_report(&sample_issues(), ReportFormat::Json, Path::new("src"));
+    let value: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(value[0]["file"], "src/guide/a.md");
+    assert_eq!(value[0]["line"], 3);
+    assert_eq!(value[0]["column"], 5);
+    assert_eq!(value[0]["rule"], "missing-file");
+    assert_eq!(value[0]["url"], "./b.md");
+}
+
+#[test]
+fn test_sarif_report() {
+    let report = render_report(&sample_issues(), ReportFormat::Sarif, Path::new("src"));
+    let value: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(value["version"], "2.1.0");
+    let run = &value["runs"][0];
+    assert_eq!(run["columnKind"], "unicodeCodePoints");
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "missing-file");
+    let result = &run["results"][0];
+    assert_eq!(result["level"], "error");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/guide/a.md");
+    assert_eq!(location["region"]["startLine"], 3);
+}
+
+#[test]
+fn test_junit_report() {
+    let report = render_report(&sample_issues(), ReportFormat::Junit, Path::new("src"));
+    assert!(report.contains(r#"failures="1""#));
+    assert!(report.contains(r#"<testcase name="src/guide/a.md:3:5 missing-file""#));
+    assert!(report.contains("[&lt;B &amp; C&gt;](./b.md)"));
+
+    let empty = render_report(&[], ReportFormat::Junit, Path::new("src"));
+    assert!(empty.contains(r#"failures="0""#));
+}
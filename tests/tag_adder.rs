@@ -62,3 +62,77 @@ $$
     assert!(content.contains(r#"<a id="sametitle-1">"#));
     assert!(!content.contains(r#"<a id="代码框内标题">"#));
 }
+
+#[test]
+fn test_add_heading_anchors_reserved() {
+    use std::collections::HashSet;
+    use tag_adder::add_heading_anchors_reserved;
+    let mut content = "# Intro\n\n# Other\n".to_string();
+    add_heading_anchors_reserved(&mut content, false, &HashSet::from(["intro".to_string()]));
+    assert!(content.contains(r#"<a id="intro-1">"#));
+    assert!(!content.contains(r#"<a id="intro">"#));
+    assert!(content.contains(r#"<a id="other">"#));
+}
+
+/// Renders the chapter the same way as mdbook (`render_markdown` + `build_header_links`),
+/// returns all IDs in the HTML.
+fn rendered_ids(content: &str) -> std::collections::HashSet<String> {
+    use regex::Regex;
+    use std::collections::HashMap;
+
+    let html = mdbook::utils::render_markdown(content, false);
+    let headings =
+        Regex::new(r#"<h(\d)(?: id="([^"]+)")?(?: class="([^"]+)")?>(.*?)</h\d>"#).unwrap();
+    let mut id_counter = HashMap::new();
+    let html = headings.replace_all(&html, |caps: &regex::Captures<'_>| {
+        let id = caps.get(2).map_or_else(
+            || mdbook::utils::unique_id_from_content(&caps[4], &mut id_counter),
+            |id| id.as_str().to_string(),
+        );
+        format!(r#"<h{0} id="{1}">{2}</h{0}>"#, &caps[1], id, &caps[4])
+    });
+    Regex::new(r#"\bid="([^"]+)""#)
+        .unwrap()
+        .captures_iter(&html)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+const RENDERED_CONTENT: &str = "# Intro\n\n# Intro {#intro}\n\n## Custom heading {#custom}\n\n\
+                                ## Classes {#classes .wide}\n\n# 中文标题\n\n# Same\n\n# Same\n\n\
+                                # my_func\n\n<a id=\"top\"></a>\n\n```md\n# Not a heading\n```\n";
+
+#[test]
+fn test_rendered_ids_match_index() {
+    use mdbook_betterlink::link_checker::anchors::AnchorIndex;
+    use std::path::{Path, PathBuf};
+    use tag_adder::add_heading_anchors_reserved;
+
+    let mut content = RENDERED_CONTENT.to_string();
+    let mut index = AnchorIndex::new(false);
+    index.insert(PathBuf::from("a.md"), &content);
+    let anchors = index.get(Path::new("a.md")).unwrap();
+    add_heading_anchors_reserved(&mut content, false, &anchors.explicit);
+
+    let rendered = rendered_ids(&content);
+    assert_eq!(rendered, anchors.all);
+    assert!(rendered.contains("custom"));
+    assert!(!rendered.contains("custom-heading"));
+}
+
+#[test]
+fn test_rendered_ids_match_index_with_old_tag_adder() {
+    use mdbook_betterlink::handler::book_handler::old_tag_adder::add_a_tag;
+    use mdbook_betterlink::link_checker::anchors::AnchorIndex;
+    use std::path::{Path, PathBuf};
+
+    let mut content = RENDERED_CONTENT.to_string();
+    let mut index = AnchorIndex::new(false).with_old_tag_adder(true);
+    index.insert(PathBuf::from("a.md"), &content);
+    let anchors = index.get(Path::new("a.md")).unwrap();
+    add_a_tag(&mut content, false);
+
+    let rendered = rendered_ids(&content);
+    assert_eq!(&rendered, &anchors.all);
+    assert!(rendered.contains("myfunc"));
+}